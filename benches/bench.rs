@@ -1,51 +1,80 @@
 use std::path::Path;
 
 use bencher::{benchmark_group, benchmark_main, Bencher};
-use path_dedot::ParseDot;
+use path_dedot::{Clean, Dedotter, ParseDot};
 
 fn no_dots(bencher: &mut Bencher) {
-    #[cfg(feature = "unsafe_cache")]
-    unsafe {
-        path_dedot::update_cwd()
-    };
-
     let path = Path::new("path/to/123/456");
 
     bencher.iter(|| path.parse_dot());
 }
 
 fn starts_with_a_single_dot(bencher: &mut Bencher) {
-    #[cfg(feature = "unsafe_cache")]
-    unsafe {
-        path_dedot::update_cwd()
-    };
-
     let path = Path::new("./path/to/123/456");
 
     bencher.iter(|| path.parse_dot());
 }
 
 fn starts_with_double_dots(bencher: &mut Bencher) {
-    #[cfg(feature = "unsafe_cache")]
-    unsafe {
-        path_dedot::update_cwd()
-    };
-
     let path = Path::new("../path/to/123/456");
 
     bencher.iter(|| path.parse_dot());
 }
 
 fn mix(bencher: &mut Bencher) {
-    #[cfg(feature = "unsafe_cache")]
-    unsafe {
-        path_dedot::update_cwd()
-    };
-
     let path = Path::new("./path/to/123/../456");
 
     bencher.iter(|| path.parse_dot());
 }
 
-benchmark_group!(bench_group, no_dots, starts_with_a_single_dot, starts_with_double_dots, mix);
+fn long_no_dots(bencher: &mut Bencher) {
+    let path = "path/to/123/456/".repeat(64);
+    let path = Path::new(&path);
+
+    bencher.iter(|| path.parse_dot_from("/cwd"));
+}
+
+fn long_mix(bencher: &mut Bencher) {
+    let path = "./path/to/../123/./456/..//".repeat(64);
+    let path = Path::new(&path);
+
+    bencher.iter(|| path.parse_dot_from("/cwd"));
+}
+
+fn long_starts_with_double_dots(bencher: &mut Bencher) {
+    let path = format!("../{}", "path/to/123/456/../".repeat(64));
+    let path = Path::new(&path);
+
+    bencher.iter(|| path.parse_dot_from("/cwd/path/to/123/456"));
+}
+
+fn long_clean(bencher: &mut Bencher) {
+    let path = "path/../../to/123/./456/".repeat(64);
+    let path = Path::new(&path);
+
+    bencher.iter(|| path.clean());
+}
+
+fn many_absolute(bencher: &mut Bencher) {
+    let paths: Vec<String> = (0..1000).map(|i| format!("/path/to/{}/../456", i)).collect();
+
+    bencher.iter(|| {
+        let mut dedotter = Dedotter::new();
+
+        dedotter.parse_dot_all(&paths).filter(Result::is_ok).count()
+    });
+}
+
+benchmark_group!(
+    bench_group,
+    no_dots,
+    starts_with_a_single_dot,
+    starts_with_double_dots,
+    mix,
+    long_no_dots,
+    long_mix,
+    long_starts_with_double_dots,
+    long_clean,
+    many_absolute
+);
 benchmark_main!(bench_group);
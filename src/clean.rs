@@ -0,0 +1,9 @@
+use std::{borrow::Cow, path::Path};
+
+/// Let `Path` and `PathBuf` have `clean` method.
+pub trait Clean {
+    /// Lexically remove dots in the path and create a new `PathBuf` instance on demand. The current working directory is never used, so this method cannot fail.
+    ///
+    /// Like Go's `filepath.Clean`, a leading **Double Dots** of a relative path is kept instead of being resolved, and an empty path or an empty result becomes a **Single Dot**. On Windows, a relative result whose first name would be parsed as a drive, e.g. `C:` in `.\C:\x`, keeps a leading `.\` so that it is not parsed as a prefix.
+    fn clean(&self) -> Cow<'_, Path>;
+}
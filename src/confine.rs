@@ -0,0 +1,69 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(not(windows))]
+use crate::posix as imp;
+#[cfg(windows)]
+use crate::windows as imp;
+use crate::{
+    path_str::bytes_to_os_str,
+    tokens::{TokenSink, TokenVec},
+    EscapePolicy, MAIN_SEPARATOR,
+};
+
+/// Join an untrusted path to `root` and make sure the result stays under `root`.
+///
+/// The root directory and the prefix (e.g. `C:` or `\\server\share`) of `untrusted` are treated as if they were not there. **Double Dots** cannot climb above `root`. These escaping components are ignored with `EscapePolicy::Clamp`, otherwise a `DedotError::Escape` telling which component of `untrusted` escapes is returned. `EscapePolicy::Preserve` is treated as `EscapePolicy::Error` because the escaping components cannot be kept under `root`. On Windows, a name which is only made of dots and spaces is treated as **Double Dots**, because Windows removes the trailing dots and spaces of a name.
+///
+/// `root` itself is used as it is. An empty `root` is treated as a **Single Dot**.
+pub fn join_confined(
+    root: impl AsRef<Path>,
+    untrusted: impl AsRef<Path>,
+    escape_policy: EscapePolicy,
+) -> io::Result<PathBuf> {
+    let root = root.as_ref();
+    let untrusted = untrusted.as_ref();
+
+    let escape_policy = match escape_policy {
+        EscapePolicy::Clamp => EscapePolicy::Clamp,
+        _ => EscapePolicy::Error,
+    };
+
+    // `root` is the root of the tokens, which cannot be popped
+    let mut tokens = TokenVec::default();
+
+    tokens.push_root();
+
+    imp::dedot_confined(
+        untrusted.as_os_str().as_encoded_bytes(),
+        escape_policy,
+        &|| untrusted.to_path_buf(),
+        &mut tokens,
+    )?;
+
+    let mut path_buf =
+        if root.as_os_str().is_empty() { PathBuf::from(".") } else { root.to_path_buf() };
+
+    if let Some((first, rest)) = tokens.tokens[1..].split_first() {
+        // make sure that the root ends with a separator, and do not use `PathBuf::push` for the tokens because they must not be parsed again
+        path_buf.push("");
+
+        let mut path_string = path_buf.into_os_string();
+
+        // the tokens are cut from `untrusted` next to separators
+        path_string.push(unsafe { bytes_to_os_str(first) });
+
+        for token in rest {
+            path_string.push(MAIN_SEPARATOR.as_os_str());
+            path_string.push(unsafe { bytes_to_os_str(token) });
+        }
+
+        path_buf = PathBuf::from(path_string);
+    }
+
+    debug_assert!(path_buf.starts_with(root));
+
+    Ok(path_buf)
+}
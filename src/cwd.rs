@@ -0,0 +1,291 @@
+use std::{
+    borrow::Cow,
+    env, io,
+    ops::Deref,
+    path::{self, Path, PathBuf},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+};
+
+use crate::DedotError;
+
+/// How the methods which are not given a current working directory, e.g. `parse_dot`, get it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CwdStrategy {
+    /// Call `std::env::current_dir` every time the CWD is needed, so changing the CWD at runtime is always seen. This is the default.
+    Fetch,
+    /// Fetch the CWD when it is needed for the first time and cache it. Use the `update_cwd` function after changing the CWD. This is the default if any of the cache features is enabled by any crate in the dependency graph, so a library which must see a changed CWD should use `ProcessCwd` instead of relying on the strategy.
+    Cache,
+}
+
+const FETCH: u8 = 0;
+const CACHE: u8 = 1;
+
+static STRATEGY: AtomicU8 = AtomicU8::new(
+    if cfg!(any(
+        feature = "once_cell_cache",
+        feature = "lazy_static_cache",
+        feature = "unsafe_cache"
+    )) {
+        CACHE
+    } else {
+        FETCH
+    },
+);
+
+/// Choose how the CWD is got for the whole process. It can be called at any time, e.g. at the start of `main`.
+#[inline]
+pub fn set_cwd_strategy(strategy: CwdStrategy) {
+    let strategy = match strategy {
+        CwdStrategy::Fetch => FETCH,
+        CwdStrategy::Cache => CACHE,
+    };
+
+    STRATEGY.store(strategy, Ordering::Relaxed);
+}
+
+/// The current `CwdStrategy`.
+#[inline]
+pub fn cwd_strategy() -> CwdStrategy {
+    match STRATEGY.load(Ordering::Relaxed) {
+        FETCH => CwdStrategy::Fetch,
+        _ => CwdStrategy::Cache,
+    }
+}
+
+/// Current working directory, which can be read and updated from any thread.
+///
+/// Readers share the cached `Arc<Path>` instead of borrowing it, so the previous CWD is freed as soon as the last `parse_dot` call which is still using it returns.
+struct CwdCache {
+    path: RwLock<Option<Arc<Path>>>,
+}
+
+impl CwdCache {
+    #[inline]
+    const fn new() -> CwdCache {
+        CwdCache {
+            path: RwLock::new(None)
+        }
+    }
+
+    #[inline]
+    fn get(&self) -> Option<Arc<Path>> {
+        self.path.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Get the cached CWD, or fetch it and cache it if it has not been cached.
+    #[inline]
+    fn get_cached(&self) -> io::Result<Arc<Path>> {
+        match self.get() {
+            Some(cwd) => Ok(cwd),
+            None => Ok(self.init(env::current_dir()?)),
+        }
+    }
+
+    /// Cache the CWD unless another thread has cached one, and return the cached one.
+    #[inline]
+    fn init(&self, cwd: PathBuf) -> Arc<Path> {
+        self.path
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(|| Arc::from(cwd))
+            .clone()
+    }
+
+    #[inline]
+    fn is_cached(&self) -> bool {
+        self.path.read().unwrap_or_else(PoisonError::into_inner).is_some()
+    }
+
+    /// Replace the cached CWD.
+    #[inline]
+    fn set(&self, cwd: PathBuf) {
+        *self.path.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::from(cwd));
+    }
+}
+
+/// Current working directory, which is cached with `CwdStrategy::Cache`. Use `CachedCwd` to read it.
+static CWD: CwdCache = CwdCache::new();
+
+/// Fetch the CWD and cache it if it has not been cached, so that the first `parse_dot` call with `CwdStrategy::Cache` does not need to fetch it. An error is returned if the CWD cannot be fetched, e.g. the CWD has been deleted, in which case nothing is cached.
+#[inline]
+pub fn init_cwd() -> io::Result<()> {
+    if !CWD.is_cached() {
+        CWD.init(env::current_dir()?);
+    }
+
+    Ok(())
+}
+
+/// Whether the CWD has been cached in the `path-dedot` crate, by the `init_cwd` function, the `update_cwd` function or a `parse_dot` call with `CwdStrategy::Cache`.
+#[inline]
+pub fn is_cwd_cached() -> bool {
+    CWD.is_cached()
+}
+
+/// Initialize or update the CWD cached in the `path-dedot` crate after using the `std::env::set_current_dir` function. It is safe to call it while `parse_dot` methods are running on other threads, which see either the previous CWD or the new one.
+#[inline]
+pub fn update_cwd() -> io::Result<()> {
+    CWD.set(env::current_dir()?);
+
+    Ok(())
+}
+
+/// The current working directory got from a `CwdProvider`. It is borrowed from the provider, owned, or shared with the cache of the `path-dedot` crate.
+#[derive(Debug, Clone)]
+pub enum CwdPath<'a> {
+    /// Borrowed from the provider, e.g. a fixed path.
+    Borrowed(&'a Path),
+    /// Fetched for this call, e.g. by `ProcessCwd`.
+    Owned(PathBuf),
+    /// The cached CWD given by `CachedCwd`, which stays alive after `update_cwd` replaces it.
+    Shared(Arc<Path>),
+}
+
+impl<'a> CwdPath<'a> {
+    /// Extract the owned path, which is cloned if it is not `CwdPath::Owned`.
+    #[inline]
+    pub fn into_owned(self) -> PathBuf {
+        match self {
+            CwdPath::Owned(path) => path,
+            _ => self.to_path_buf(),
+        }
+    }
+}
+
+impl<'a> Deref for CwdPath<'a> {
+    type Target = Path;
+
+    #[inline]
+    fn deref(&self) -> &Path {
+        match self {
+            CwdPath::Borrowed(path) => path,
+            CwdPath::Owned(path) => path,
+            CwdPath::Shared(path) => path,
+        }
+    }
+}
+
+impl<'a> AsRef<Path> for CwdPath<'a> {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        self
+    }
+}
+
+impl<'a> From<&'a Path> for CwdPath<'a> {
+    #[inline]
+    fn from(path: &'a Path) -> Self {
+        CwdPath::Borrowed(path)
+    }
+}
+
+impl<'a> From<PathBuf> for CwdPath<'a> {
+    #[inline]
+    fn from(path: PathBuf) -> Self {
+        CwdPath::Owned(path)
+    }
+}
+
+impl<'a> From<Arc<Path>> for CwdPath<'a> {
+    #[inline]
+    fn from(path: Arc<Path>) -> Self {
+        CwdPath::Shared(path)
+    }
+}
+
+impl<'a> From<Cow<'a, Path>> for CwdPath<'a> {
+    #[inline]
+    fn from(path: Cow<'a, Path>) -> Self {
+        match path {
+            Cow::Borrowed(path) => CwdPath::Borrowed(path),
+            Cow::Owned(path) => CwdPath::Owned(path),
+        }
+    }
+}
+
+/// Where the methods which are not given a current working directory get it. The methods with a `_with_provider` suffix, e.g. `parse_dot_with_provider`, can be given any provider, and the other methods use `DefaultCwd`.
+///
+/// The provider is only asked when a path starts with a **Single Dot** or **Double Dots**. Its error is wrapped in `DedotError::CwdUnavailable`.
+pub trait CwdProvider {
+    /// Get the current working directory.
+    fn cwd(&self) -> io::Result<CwdPath<'_>>;
+}
+
+/// The CWD of the process, which is got with the current `CwdStrategy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DefaultCwd;
+
+impl CwdProvider for DefaultCwd {
+    #[inline]
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        match cwd_strategy() {
+            CwdStrategy::Fetch => ProcessCwd.cwd(),
+            CwdStrategy::Cache => CachedCwd.cwd(),
+        }
+    }
+}
+
+/// The CWD of the process, which is fetched by the `std::env::current_dir` function every time, whatever the current `CwdStrategy` is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ProcessCwd;
+
+impl CwdProvider for ProcessCwd {
+    #[inline]
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        env::current_dir().map(CwdPath::Owned)
+    }
+}
+
+/// The CWD cached in the `path-dedot` crate, whatever the current `CwdStrategy` is. It is fetched and cached if it has not been cached, and the `update_cwd` function refreshes it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CachedCwd;
+
+impl CwdProvider for CachedCwd {
+    #[inline]
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        CWD.get_cached().map(CwdPath::Shared)
+    }
+}
+
+/// A fixed path, e.g. the logical working directory of a request, or a fake CWD in tests.
+impl CwdProvider for Path {
+    #[inline]
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        Ok(CwdPath::Borrowed(self))
+    }
+}
+
+impl CwdProvider for PathBuf {
+    #[inline]
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        Ok(CwdPath::Borrowed(self))
+    }
+}
+
+impl<T: CwdProvider + ?Sized> CwdProvider for &T {
+    #[inline]
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        (**self).cwd()
+    }
+}
+
+/// Get the CWD from the provider for the path, whose leading **Single Dot** or **Double Dots** needs it. The error is wrapped in a `DedotError`.
+pub(crate) fn provide_cwd<'c>(
+    provider: &'c (impl CwdProvider + ?Sized),
+    path: &Path,
+) -> io::Result<CwdPath<'c>> {
+    provider.cwd().map_err(|source| {
+        // the dots follow the prefix on Windows
+        let index =
+            usize::from(matches!(path.components().next(), Some(path::Component::Prefix(_))));
+
+        io::Error::from(DedotError::CwdUnavailable {
+            path: path.to_path_buf(),
+            index,
+            source,
+        })
+    })
+}
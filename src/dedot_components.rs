@@ -0,0 +1,72 @@
+use std::{
+    iter::FusedIterator,
+    path::{Component, Path},
+    vec,
+};
+
+use crate::{path_str::bytes_to_os_str, tokens::TokenVec};
+
+/// An iterator over the components of a dedotted path, created by the `dedot_components` method.
+///
+/// The components are borrowed from the path and the current working directory, so the dedotted path is never joined into a new `PathBuf`.
+#[derive(Debug, Clone)]
+pub struct DedotComponents<'a> {
+    tokens:        vec::IntoIter<&'a [u8]>,
+    index:         usize,
+    has_prefix:    bool,
+    first_is_root: bool,
+}
+
+impl<'a> DedotComponents<'a> {
+    /// Create an iterator over the tokens pushed into `tokens` from the bytes of `Path`s.
+    #[inline]
+    pub(crate) fn new(tokens: TokenVec<'a>) -> DedotComponents<'a> {
+        DedotComponents {
+            tokens:        tokens.tokens.into_iter(),
+            index:         0,
+            has_prefix:    tokens.has_prefix,
+            first_is_root: tokens.has_root,
+        }
+    }
+}
+
+impl<'a> Iterator for DedotComponents<'a> {
+    type Item = Component<'a>;
+
+    fn next(&mut self) -> Option<Component<'a>> {
+        let token = self.tokens.next()?;
+
+        let index = self.index;
+
+        self.index += 1;
+
+        // SAFETY: the tokens are cut from the bytes of `Path`s next to separators, or are ASCII
+        let token = unsafe { bytes_to_os_str(token) };
+
+        if index == 0 && self.has_prefix {
+            // let `std::path` parse the prefix, because `PrefixComponent` cannot be constructed
+            if let Some(component) = Path::new(token).components().next() {
+                return Some(component);
+            }
+        }
+
+        if index == usize::from(self.has_prefix) && self.first_is_root {
+            return Some(Component::RootDir);
+        }
+
+        Some(match token.as_encoded_bytes() {
+            b"." => Component::CurDir,
+            b".." => Component::ParentDir,
+            _ => Component::Normal(token),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
+
+impl ExactSizeIterator for DedotComponents<'_> {}
+
+impl FusedIterator for DedotComponents<'_> {}
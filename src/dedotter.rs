@@ -0,0 +1,81 @@
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{cwd, native, DedotOptions, DefaultCwd};
+
+/// Remove dots in many paths with the same options. The current working directory is fetched at most once, and only when a path needs it, so dedotting absolute paths never calls `std::env::current_dir`.
+#[derive(Debug, Clone, Default)]
+pub struct Dedotter {
+    options: DedotOptions,
+    cwd:     Option<PathBuf>,
+}
+
+impl Dedotter {
+    /// Create a `Dedotter` which behaves the same as the `parse_dot` method.
+    #[inline]
+    pub fn new() -> Dedotter {
+        Dedotter::default()
+    }
+
+    /// Set the options, which behave the same as those of the `parse_dot_with` method.
+    #[inline]
+    pub fn options(mut self, options: DedotOptions) -> Dedotter {
+        self.options = options;
+
+        self
+    }
+
+    /// Use the given current working directory instead of fetching it.
+    #[inline]
+    pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> Dedotter {
+        self.cwd = Some(cwd.into());
+
+        self
+    }
+
+    /// Whether the current working directory has been given or fetched.
+    #[inline]
+    pub fn has_cwd(&self) -> bool {
+        self.cwd.is_some()
+    }
+
+    /// Remove dots in the path and create a new `PathBuf` instance on demand, like the `parse_dot_with` method. The current working directory is fetched if it is needed for the first time.
+    pub fn parse_dot<'a, P: AsRef<Path> + ?Sized>(
+        &mut self,
+        path: &'a P,
+    ) -> io::Result<Cow<'a, Path>> {
+        let path = path.as_ref();
+
+        let options = self.options;
+
+        native::parse_dot_lazy(path, options, || self.get_cwd(path))
+    }
+
+    /// Remove dots in every path and iterate over the results in the same order.
+    #[inline]
+    pub fn parse_dot_all<'s, 'a: 's, I, P>(
+        &'s mut self,
+        paths: I,
+    ) -> impl Iterator<Item = io::Result<Cow<'a, Path>>> + 's
+    where
+        I: IntoIterator<Item = &'a P>,
+        I::IntoIter: 's,
+        P: AsRef<Path> + ?Sized + 'a, {
+        paths.into_iter().map(move |path| self.parse_dot(path))
+    }
+
+    #[inline]
+    fn get_cwd(&mut self, path: &Path) -> io::Result<&Path> {
+        match self.cwd {
+            Some(ref cwd) => Ok(cwd),
+            None => {
+                let cwd = cwd::provide_cwd(&DefaultCwd, path)?.into_owned();
+
+                Ok(self.cwd.insert(cwd))
+            },
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env,
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt::{self, Display, Formatter},
+    hash::BuildHasher,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use crate::{DedotOptions, ParseDot};
+
+/// The syntax of the variables in a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VarSyntax {
+    /// `$VAR`, `${VAR}`, `${VAR-default}` and `${VAR:-default}`.
+    Posix,
+    /// `%VAR%`.
+    Windows,
+}
+
+/// How to handle variables which are not defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UndefinedVar {
+    /// Return `VarError::Undefined`.
+    Error,
+    /// Expand the variable to an empty string.
+    Empty,
+}
+
+/// A source of variables.
+pub trait VarSource {
+    /// Get the value of the variable named `name`.
+    fn get(&self, name: &str) -> Option<OsString>;
+}
+
+/// The environment variables of the current process.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl VarSource for ProcessEnv {
+    #[inline]
+    fn get(&self, name: &str) -> Option<OsString> {
+        env::var_os(name)
+    }
+}
+
+impl<F: Fn(&str) -> Option<OsString>> VarSource for F {
+    #[inline]
+    fn get(&self, name: &str) -> Option<OsString> {
+        self(name)
+    }
+}
+
+impl<V: AsRef<OsStr>, H: BuildHasher> VarSource for HashMap<String, V, H> {
+    #[inline]
+    fn get(&self, name: &str) -> Option<OsString> {
+        HashMap::get(self, name).map(|v| v.as_ref().to_os_string())
+    }
+}
+
+/// The error returned when the variables in a path cannot be expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VarError {
+    /// The variable is not defined and `UndefinedVar::Error` is used.
+    Undefined(String),
+    /// The variable reference starting at the byte offset is not closed or has an invalid name.
+    Invalid(usize),
+    /// The name of a variable in `%VAR%` is not valid UTF-8.
+    NotUtf8,
+}
+
+impl Display for VarError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            VarError::Undefined(name) => write!(f, "the variable {:?} is not defined", name),
+            VarError::Invalid(offset) => {
+                write!(f, "the variable reference at offset {} is invalid", offset)
+            },
+            VarError::NotUtf8 => f.write_str("the name of a variable is not valid UTF-8"),
+        }
+    }
+}
+
+impl Error for VarError {}
+
+impl From<VarError> for io::Error {
+    #[inline]
+    fn from(error: VarError) -> Self {
+        io::Error::new(ErrorKind::InvalidInput, error)
+    }
+}
+
+/// Expand the variables in paths, and optionally remove dots after that.
+///
+/// The expanded text is parsed as a path again, so separators and dots in the values of the variables are handled as if they were written in the path.
+#[derive(Debug, Clone)]
+pub struct VarExpander<S: VarSource = ProcessEnv> {
+    syntax:    VarSyntax,
+    undefined: UndefinedVar,
+    source:    S,
+}
+
+impl VarExpander {
+    /// Create a `VarExpander` which reads the environment variables of the current process. Undefined variables are errors.
+    #[inline]
+    pub fn new(syntax: VarSyntax) -> VarExpander {
+        VarExpander::with_source(syntax, ProcessEnv)
+    }
+}
+
+impl<S: VarSource> VarExpander<S> {
+    /// Create a `VarExpander` which reads the variables from `source`. Undefined variables are errors.
+    #[inline]
+    pub fn with_source(syntax: VarSyntax, source: S) -> VarExpander<S> {
+        VarExpander {
+            syntax,
+            undefined: UndefinedVar::Error,
+            source,
+        }
+    }
+
+    /// Set how to handle variables which are not defined.
+    #[inline]
+    pub fn undefined(mut self, undefined: UndefinedVar) -> VarExpander<S> {
+        self.undefined = undefined;
+
+        self
+    }
+
+    /// Expand the variables in the path. The path is returned as it is if it has no variables.
+    pub fn expand<'a>(&self, path: &'a Path) -> Result<Cow<'a, Path>, VarError> {
+        let bytes = path.as_os_str().as_encoded_bytes();
+
+        let marker = match self.syntax {
+            VarSyntax::Posix => b'$',
+            VarSyntax::Windows => b'%',
+        };
+
+        if !bytes.contains(&marker) {
+            return Ok(Cow::from(path));
+        }
+
+        let mut output = Vec::with_capacity(bytes.len());
+
+        match self.syntax {
+            VarSyntax::Posix => self.expand_posix(bytes, 0, &mut output)?,
+            VarSyntax::Windows => self.expand_windows(bytes, &mut output)?,
+        }
+
+        Ok(Cow::from(PathBuf::from(bytes_to_os_string(output))))
+    }
+
+    /// Expand the variables in the path and then remove dots in it with the `parse_dot_from_with` method.
+    pub fn parse_dot_from<'a>(
+        &self,
+        path: &'a Path,
+        cwd: impl AsRef<Path>,
+        options: DedotOptions,
+    ) -> io::Result<Cow<'a, Path>> {
+        match self.expand(path)? {
+            Cow::Borrowed(path) => path.parse_dot_from_with(cwd, options),
+            Cow::Owned(path) => Ok(Cow::from(path.parse_dot_from_with(cwd, options)?.into_owned())),
+        }
+    }
+
+    fn lookup(&self, name: &str, output: &mut Vec<u8>) -> Result<bool, VarError> {
+        match self.source.get(name) {
+            Some(value) => {
+                output.extend_from_slice(value.as_encoded_bytes());
+
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn undefined_var(&self, name: &str) -> Result<(), VarError> {
+        match self.undefined {
+            UndefinedVar::Error => Err(VarError::Undefined(name.to_string())),
+            UndefinedVar::Empty => Ok(()),
+        }
+    }
+
+    /// `offset` is the position of `bytes` in the whole path, for error reporting.
+    fn expand_posix(
+        &self,
+        bytes: &[u8],
+        offset: usize,
+        output: &mut Vec<u8>,
+    ) -> Result<(), VarError> {
+        let length = bytes.len();
+
+        let mut i = 0;
+
+        while i < length {
+            let b = bytes[i];
+
+            if b != b'$' || i + 1 == length {
+                output.push(b);
+
+                i += 1;
+
+                continue;
+            }
+
+            if bytes[i + 1] == b'{' {
+                let start = i;
+
+                // find the matching brace
+                let mut depth = 0usize;
+                let mut end = None;
+
+                let mut j = i + 2;
+
+                while j < length {
+                    match bytes[j] {
+                        b'{' if bytes[j - 1] == b'$' => depth += 1,
+                        b'}' => {
+                            if depth == 0 {
+                                end = Some(j);
+
+                                break;
+                            }
+
+                            depth -= 1;
+                        },
+                        _ => (),
+                    }
+
+                    j += 1;
+                }
+
+                let end = end.ok_or(VarError::Invalid(offset + start))?;
+
+                let inner = &bytes[start + 2..end];
+
+                let name_length = inner.iter().take_while(|&&b| is_name_byte(b)).count();
+
+                let name = std::str::from_utf8(&inner[..name_length]).unwrap();
+
+                let rest = &inner[name_length..];
+
+                let (default, use_default_if_empty) = if rest.starts_with(b":-") {
+                    (Some(&rest[2..]), true)
+                } else if rest.starts_with(b"-") {
+                    (Some(&rest[1..]), false)
+                } else if rest.is_empty() && !name.is_empty() {
+                    (None, false)
+                } else {
+                    return Err(VarError::Invalid(offset + start));
+                };
+
+                let value = self.source.get(name);
+
+                let value = match value {
+                    Some(value) if !(use_default_if_empty && value.is_empty()) => Some(value),
+                    _ => None,
+                };
+
+                match (value, default) {
+                    (Some(value), _) => output.extend_from_slice(value.as_encoded_bytes()),
+                    (None, Some(default)) => {
+                        self.expand_posix(default, offset + start + 2 + name_length, output)?
+                    },
+                    (None, None) => self.undefined_var(name)?,
+                }
+
+                i = end + 1;
+            } else {
+                let name_length = bytes[i + 1..].iter().take_while(|&&b| is_name_byte(b)).count();
+
+                if name_length == 0 || bytes[i + 1].is_ascii_digit() {
+                    output.push(b);
+
+                    i += 1;
+
+                    continue;
+                }
+
+                let name = std::str::from_utf8(&bytes[i + 1..i + 1 + name_length]).unwrap();
+
+                if !self.lookup(name, output)? {
+                    self.undefined_var(name)?;
+                }
+
+                i += 1 + name_length;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expand_windows(&self, bytes: &[u8], output: &mut Vec<u8>) -> Result<(), VarError> {
+        let length = bytes.len();
+
+        let mut i = 0;
+
+        while i < length {
+            let b = bytes[i];
+
+            if b == b'%' {
+                if let Some(name_length) = bytes[i + 1..].iter().position(|&b| b == b'%') {
+                    if name_length > 0 {
+                        let name = std::str::from_utf8(&bytes[i + 1..i + 1 + name_length])
+                            .map_err(|_| VarError::NotUtf8)?;
+
+                        if !self.lookup(name, output)? {
+                            self.undefined_var(name)?;
+                        }
+
+                        i += name_length + 2;
+
+                        continue;
+                    }
+                }
+            }
+
+            output.push(b);
+
+            i += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Every variable reference is cut next to ASCII characters, so the encoded bytes of the path and of the values can be scanned and joined without requiring UTF-8.
+#[inline]
+fn bytes_to_os_string(bytes: Vec<u8>) -> OsString {
+    // SAFETY: the bytes are a mixture of the encoded bytes of `OsStr`s, cut next to ASCII characters
+    unsafe { OsString::from_encoded_bytes_unchecked(bytes) }
+}
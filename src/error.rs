@@ -0,0 +1,182 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use crate::{EscapeError, SymlinkError, TildeError};
+
+/// The error returned by the `ParseDot` methods, wrapped in an `io::Error`. Use the `DedotError::downcast_ref` function to get it back.
+///
+/// There is no variant for input which is not valid UTF-8 or for an invalid prefix. Paths are dedotted as the encoded bytes of `OsStr`, which can be any path of the platform, and prefixes are parsed in the same way as `std::path`, which takes anything it cannot recognize as a prefix as an ordinary component.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DedotError {
+    /// The current working directory is needed for the component at `index` of the path, but it cannot be fetched.
+    CwdUnavailable { path: PathBuf, index: usize, source: io::Error },
+    /// The home directory for the leading `~` of the path cannot be found with the `expand_tilde` option. Only the first component is expanded, so the index is always `0`.
+    Tilde { path: PathBuf, source: TildeError },
+    /// **Double Dots** climbs above the start of the path under `EscapePolicy::Error`.
+    Escape(EscapeError),
+    /// **Double Dots** would remove a symbolic link under `SymlinkPolicy::Error`.
+    Symlink(SymlinkError),
+    /// The dedotted path and base of the `relative_to` method do not have the same prefix and root. `index` is that of the first prefix or root which differs.
+    PrefixMismatch { path: PathBuf, base: PathBuf, index: usize },
+    /// The dedotted base of the `relative_to` method has a component which is not a name at `index`, so the way back from it cannot be known.
+    Unresolvable { path: PathBuf, index: usize },
+    /// The relative path cannot be resolved on the file system against the current working directory given to the `parse_dot_physical_from` method, because that is relative as well.
+    RelativeCwd { path: PathBuf, cwd: PathBuf },
+}
+
+impl DedotError {
+    /// Get the `DedotError` in an `io::Error` returned by the `ParseDot` methods. Other errors, e.g. those from the file system, are not `DedotError`s.
+    #[inline]
+    pub fn downcast_ref(error: &io::Error) -> Option<&DedotError> {
+        error.get_ref().and_then(|error| error.downcast_ref())
+    }
+
+    /// The path which cannot be dedotted.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        match self {
+            DedotError::CwdUnavailable {
+                path, ..
+            }
+            | DedotError::Tilde {
+                path, ..
+            }
+            | DedotError::PrefixMismatch {
+                path, ..
+            }
+            | DedotError::Unresolvable {
+                path, ..
+            }
+            | DedotError::RelativeCwd {
+                path, ..
+            } => path.as_path(),
+            DedotError::Escape(error) => error.path(),
+            DedotError::Symlink(error) => error.path(),
+        }
+    }
+
+    /// The index of the offending component in the components of the path. The prefix or the root is the first component, and it is always `0` for `Tilde` and `RelativeCwd`.
+    #[inline]
+    pub fn index(&self) -> usize {
+        match self {
+            DedotError::CwdUnavailable {
+                index, ..
+            }
+            | DedotError::PrefixMismatch {
+                index, ..
+            }
+            | DedotError::Unresolvable {
+                index, ..
+            } => *index,
+            DedotError::Tilde {
+                ..
+            }
+            | DedotError::RelativeCwd {
+                ..
+            } => 0,
+            DedotError::Escape(error) => error.index(),
+            DedotError::Symlink(error) => error.index(),
+        }
+    }
+}
+
+impl Display for DedotError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DedotError::CwdUnavailable {
+                path,
+                index,
+                ..
+            } => write!(
+                f,
+                "the current working directory for the component at index {} of {:?} cannot be \
+                 fetched",
+                index, path
+            ),
+            DedotError::Tilde {
+                path, ..
+            } => write!(f, "the home directory for {:?} cannot be found", path),
+            DedotError::Escape(error) => Display::fmt(error, f),
+            DedotError::Symlink(error) => Display::fmt(error, f),
+            DedotError::PrefixMismatch {
+                path,
+                base,
+                ..
+            } => write!(
+                f,
+                "{:?} and {:?} do not have the same prefix and root, so one cannot be relative to \
+                 the other",
+                path, base
+            ),
+            DedotError::Unresolvable {
+                path,
+                index,
+            } => write!(
+                f,
+                "the component at index {} of {:?} cannot be resolved to get a path relative to it",
+                index, path
+            ),
+            DedotError::RelativeCwd {
+                path,
+                cwd,
+            } => write!(
+                f,
+                "{:?} cannot be resolved on the file system because the current working directory \
+                 {:?} is relative",
+                path, cwd
+            ),
+        }
+    }
+}
+
+impl Error for DedotError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DedotError::CwdUnavailable {
+                source, ..
+            } => Some(source),
+            DedotError::Tilde {
+                source, ..
+            } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<EscapeError> for DedotError {
+    #[inline]
+    fn from(error: EscapeError) -> Self {
+        DedotError::Escape(error)
+    }
+}
+
+impl From<SymlinkError> for DedotError {
+    #[inline]
+    fn from(error: SymlinkError) -> Self {
+        DedotError::Symlink(error)
+    }
+}
+
+impl From<DedotError> for io::Error {
+    #[inline]
+    fn from(error: DedotError) -> Self {
+        let kind = match &error {
+            DedotError::CwdUnavailable {
+                source, ..
+            } => source.kind(),
+            DedotError::Tilde {
+                ..
+            } => ErrorKind::NotFound,
+            _ => ErrorKind::InvalidInput,
+        };
+
+        io::Error::new(kind, error)
+    }
+}
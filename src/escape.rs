@@ -0,0 +1,102 @@
+use std::{
+    error::Error,
+    ffi::OsStr,
+    fmt::{self, Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::DedotError;
+
+/// How to handle **Double Dots** which climb above the start of the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EscapePolicy {
+    /// Ignore the **Double Dots**, e.g. `path/../../../x` becomes `x` and `/path/../../x` becomes `/x`. This is what the `parse_dot` method does.
+    Clamp,
+    /// Return a `DedotError::Escape`, wrapped in an `io::Error` whose kind is `ErrorKind::InvalidInput`.
+    Error,
+    /// Keep the leading **Double Dots** in a relative output, e.g. `path/../../../x` becomes `../../x`. The **Double Dots** climbing above the root of an absolute path are still ignored.
+    Preserve,
+}
+
+impl Default for EscapePolicy {
+    #[inline]
+    fn default() -> Self {
+        EscapePolicy::Clamp
+    }
+}
+
+impl EscapePolicy {
+    /// Handle the **Double Dots** at `index` of the components of the path, which cannot pop a token. The path is only created for the error.
+    pub(crate) fn escape(
+        self,
+        path: impl FnOnce() -> PathBuf,
+        index: usize,
+        tokens: &mut impl ParentDirTokens,
+        rooted: bool,
+    ) -> io::Result<()> {
+        match self {
+            EscapePolicy::Clamp => (),
+            EscapePolicy::Error => {
+                return Err(DedotError::Escape(EscapeError {
+                    path: path(),
+                    index,
+                })
+                .into())
+            },
+            EscapePolicy::Preserve => {
+                if !rooted {
+                    tokens.push_parent_dir();
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Tokens of a path into which **Double Dots** can be pushed.
+pub(crate) trait ParentDirTokens {
+    fn push_parent_dir(&mut self);
+}
+
+impl ParentDirTokens for Vec<&OsStr> {
+    #[inline]
+    fn push_parent_dir(&mut self) {
+        self.push(OsStr::new(".."));
+    }
+}
+
+/// The error returned when a path climbs above its start under `EscapePolicy::Error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscapeError {
+    path:  PathBuf,
+    index: usize,
+}
+
+impl EscapeError {
+    /// The path which escapes.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// The index of the escaping **Double Dots** in the components of the path.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Display for EscapeError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the component at index {} of {:?} climbs above the start of the path",
+            self.index, self.path
+        )
+    }
+}
+
+impl Error for EscapeError {}
@@ -17,12 +17,6 @@ use path_dedot::*;
 
 let p = Path::new("./path/to/123/456");
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 assert_eq!(Path::join(env::current_dir().unwrap().as_path(), Path::new("path/to/123/456")).to_str().unwrap(), p.parse_dot().unwrap().to_str().unwrap());
 # }
 ```
@@ -42,12 +36,6 @@ let cwd = env::current_dir().unwrap();
 let cwd_parent = cwd.parent();
 
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 match cwd_parent {
    Some(cwd_parent) => {
       assert_eq!(Path::join(&cwd_parent, Path::new("path/to/123/456")).to_str().unwrap(), p.parse_dot().unwrap().to_str().unwrap());
@@ -69,12 +57,6 @@ use path_dedot::*;
 let p = Path::new("/path/to/../123/456/./777");
 
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 assert_eq!("/path/123/456/777", p.parse_dot().unwrap().to_str().unwrap());
 # }
 ```
@@ -87,12 +69,6 @@ use path_dedot::*;
 let p = Path::new("/path/to/../123/456/./777/..");
 
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 assert_eq!("/path/123/456", p.parse_dot().unwrap().to_str().unwrap());
 # }
 ```
@@ -107,12 +83,6 @@ use path_dedot::*;
 let p = Path::new("path/to/../123/456/./777/..");
 
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 assert_eq!("path/123/456", p.parse_dot().unwrap().to_str().unwrap());
 # }
 ```
@@ -127,12 +97,6 @@ use path_dedot::*;
 let p = Path::new("path/to/../../../../123/456/./777/..");
 
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 assert_eq!("123/456", p.parse_dot().unwrap().to_str().unwrap());
 # }
 ```
@@ -145,12 +109,6 @@ use path_dedot::*;
 let p = Path::new("/path/to/../../../../123/456/./777/..");
 
 # if cfg!(unix) {
-# #[cfg(feature = "unsafe_cache")]
-# {
-#     unsafe {
-#         update_cwd();
-#     }
-# }
 assert_eq!("/123/456", p.parse_dot().unwrap().to_str().unwrap());
 # }
 ```
@@ -171,53 +129,340 @@ let cwd = env::current_dir().unwrap();
 println!("{}", p.parse_dot_from(cwd).unwrap().to_str().unwrap());
 ```
 
-## Caching
+### Getting the current working directory from a provider
 
-By default, the `parse_dot` method creates a new `PathBuf` instance of the CWD every time in its operation. The overhead is obvious. Although it allows us to safely change the CWD at runtime by the program itself (e.g. using the `std::env::set_current_dir` function) or outside controls (e.g. using gdb to call `chdir`), we don't need that in most cases.
+With the `parse_dot_with_provider` method, the CWD is got from a `CwdProvider`, only if the path starts with a **Single Dot** or **Double Dots**. A `Path` or a `PathBuf` is a provider of itself, `ProcessCwd` fetches the CWD of the process every time, `CachedCwd` uses the cached one, and `DefaultCwd`, which the `parse_dot` method uses, follows the current `CwdStrategy`. Other providers, e.g. the logical working directory of each request in a server, can implement the trait, which returns a `CwdPath` borrowed from the provider, owned or shared.
 
-In order to parse paths with better performance, this crate provides three ways to cache the CWD.
+```rust
+use std::{io, path::{Path, PathBuf}};
 
-### once_cell_cache
+use path_dedot::*;
 
-Enabling the `once_cell_cache` feature can let this crate use `once_cell` to cache the CWD. It's thread-safe and does not need to modify any code, but once the CWD is cached, it cannot be changed anymore at runtime.
+struct Tenant {
+    home: PathBuf,
+}
 
-```toml
-[dependencies.path-dedot]
-version = "*"
-features = ["once_cell_cache"]
+impl CwdProvider for Tenant {
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        Ok(CwdPath::Borrowed(&self.home))
+    }
+}
+
+# if cfg!(unix) {
+let tenant = Tenant { home: PathBuf::from("/srv/tenant") };
+
+let p = Path::new("../other/123");
+
+assert_eq!("/srv/other/123", p.parse_dot_with_provider(&tenant, DedotOptions::new()).unwrap().to_str().unwrap());
+assert_eq!("/other/123", p.parse_dot_with_provider(Path::new("/srv"), DedotOptions::new()).unwrap().to_str().unwrap());
+# }
 ```
 
-### lazy_static_cache
+### Handling Double Dots which climb above the start
 
-Enabling the `lazy_static_cache` feature can let this crate use `lazy_static` to cache the CWD. It's thread-safe and does not need to modify any code, but once the CWD is cached, it cannot be changed anymore at runtime.
+With the `parse_dot_with` and `parse_dot_from_with` methods, you can choose an `EscapePolicy` to decide what happens to the **Double Dots** which climb above the start of the path. `EscapePolicy::Clamp` ignores them, just like the `parse_dot` method. `EscapePolicy::Error` returns a `DedotError::Escape` telling which component escapes. `EscapePolicy::Preserve` keeps them in a relative output.
 
-```toml
-[dependencies.path-dedot]
-version = "*"
-features = ["lazy_static_cache"]
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# if cfg!(unix) {
+let p = Path::new("path/to/../../../../123/456");
+
+assert_eq!("123/456", p.parse_dot_from_with("/", EscapePolicy::Clamp.into()).unwrap().to_str().unwrap());
+assert_eq!("../../123/456", p.parse_dot_from_with("/", EscapePolicy::Preserve.into()).unwrap().to_str().unwrap());
+
+let error = p.parse_dot_from_with("/", EscapePolicy::Error.into()).unwrap_err();
+let error = DedotError::downcast_ref(&error).unwrap();
+
+assert_eq!(4, error.index());
+# }
 ```
 
-### unsafe_cache
+### Joining an untrusted path under a root
 
-Enabling the `unsafe_cache` feature can let this crate use a mutable static variable to cache the CWD. It allows the program to change the CWD at runtime by the program itself, but it's not thread-safe.
+With the `join_confined` function, an untrusted path can be joined to a root directory without being able to escape from it. The root directory, the prefix and the **Double Dots** climbing above the root are either ignored or rejected.
 
-You need to use the `update_cwd` function to initialize the CWD first. The function should also be used to update the CWD after the CWD is changed.
+```rust
+use path_dedot::*;
 
-```toml
-[dependencies.path-dedot]
-version = "*"
-features = ["unsafe_cache"]
+# if cfg!(unix) {
+assert_eq!("/srv/upload/etc/passwd", join_confined("/srv/upload", "/etc/passwd", EscapePolicy::Clamp).unwrap().to_str().unwrap());
+assert_eq!("/srv/upload/passwd", join_confined("/srv/upload", "../../passwd", EscapePolicy::Clamp).unwrap().to_str().unwrap());
+assert!(join_confined("/srv/upload", "../../passwd", EscapePolicy::Error).is_err());
+# }
 ```
 
+### Resolving symbolic links followed by Double Dots
+
+The `parse_dot` method is purely lexical, so `symlink/..` may not be the path the operating system goes to. The `parse_dot_physical` method of the `ParseDotFs` trait canonicalizes the longest existing prefix of the path and lexically removes dots in the rest of the path, which does not need to exist. On Windows, the result is a verbatim path, e.g. `\\?\C:\path`, like the result of `std::fs::canonicalize`.
+
 ```rust
 use std::path::Path;
 
 use path_dedot::*;
 
-# #[cfg(feature = "unsafe_cache")]
-unsafe {
-    update_cwd();
-}
+# if cfg!(unix) {
+let p = Path::new("/path/to/../123/456/./777/..");
+
+println!("{}", p.parse_dot_physical().unwrap().to_str().unwrap());
+# }
+```
+
+### Refusing to cross symbolic links
+
+The `parse_dot_symlink_aware` and `parse_dot_from_symlink_aware` methods check each component which is about to be removed by **Double Dots**. If it is a symbolic link, the **Double Dots** is kept with `SymlinkPolicy::Keep`, or a `DedotError::Symlink` is returned with `SymlinkPolicy::Error`. The checked components are reported in the result.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# if cfg!(unix) {
+let result = Path::new("/path-dedot-missing/to/../123").parse_dot_from_symlink_aware("/", SymlinkPolicy::Keep).unwrap();
+
+assert_eq!("/path-dedot-missing/123", result.path.to_str().unwrap());
+assert_eq!("/path-dedot-missing/to", result.checked[0].path.to_str().unwrap());
+assert!(!result.checked[0].is_symlink);
+# }
+```
+
+### Expanding the home directory
+
+A leading `~` or `~user` is an ordinary file name for the `parse_dot` method. With the `expand_tilde` option, it is expanded to the home directory before dots are removed. The `expand_tilde` function can also be used alone.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# if cfg!(unix) {
+# std::env::set_var("HOME", "/home/magiclen");
+let options = DedotOptions::new().expand_tilde(true);
+
+assert_eq!("/home/magiclen/123", Path::new("~/path/../123").parse_dot_from_with("/", options).unwrap().to_str().unwrap());
+# }
+```
+
+### Expanding variables
+
+With `VarExpander`, the variables in a path (`$VAR`, `${VAR:-default}` or `%VAR%`) can be expanded before dots are removed. The values are parsed as a part of the path, so the **Double Dots** in them are handled by the same rules. The variables can come from the environment of the process or from any `VarSource`.
+
+```rust
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::Path;
+
+use path_dedot::*;
+
+# if cfg!(unix) {
+let mut source = HashMap::new();
+source.insert("DATA_DIR".to_string(), OsString::from("/srv/data"));
+
+let expander = VarExpander::with_source(VarSyntax::Posix, source);
+
+assert_eq!("/srv/logs", expander.parse_dot_from(Path::new("$DATA_DIR/../logs"), "/", DedotOptions::new()).unwrap().to_str().unwrap());
+# }
+```
+
+### Keeping the trailing separator
+
+The trailing separator of a path, and the directory-ness implied by a final **Single Dot** or **Double Dots**, is dropped by default. With the `keep_trailing_separator` option, the result ends with a separator if the input does.
+
+```rust
+# if cfg!(unix) {
+use std::path::Path;
+
+use path_dedot::*;
+
+let options = DedotOptions::new().keep_trailing_separator(true);
+
+assert_eq!("/path/to/", Path::new("/path/to/dir/..").parse_dot_from_with("/", options).unwrap().to_str().unwrap());
+assert_eq!("/path/to/dir/", Path::new("/path/to/dir/").parse_dot_from_with("/", options).unwrap().to_str().unwrap());
+# }
+```
+
+### Computing a relative path
+
+With the `relative_to` method, you can get a path as seen from another path. Both paths are dedotted with the given CWD first.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# if cfg!(unix) {
+assert_eq!("../../x/y", Path::new("/a/b/x/./y").relative_to("/a/b/c/d", "/").unwrap().to_str().unwrap());
+assert_eq!("../foo/x", Path::new("./x").relative_to("../y", "/foo").unwrap().to_str().unwrap());
+# }
+```
+
+### Iterating over the dedotted components
+
+With the `dedot_components` method, you can walk the components of the dedotted path, with the components of the CWD spliced in for a leading **Single Dot** or **Double Dots**, without joining them into a new `PathBuf`.
+
+```rust
+use std::path::{Component, Path};
+
+use path_dedot::*;
+
+# #[cfg(not(windows))]
+# {
+let names: Vec<_> = Path::new("../x/./y/../z").dedot_components(Path::new("/a/b")).unwrap().filter_map(|c| match c {
+    Component::Normal(name) => name.to_str(),
+    _ => None,
+}).collect();
+
+assert_eq!(vec!["a", "x", "z"], names);
+# }
+```
+
+### Dedotting a `PathBuf` in place
+
+With the `dedot_in_place` method of the `ParseDotMut` trait, a `PathBuf` or an `OsString` is dedotted by rewriting its own buffer. Bytes are shifted and truncated, and the buffer only grows when a leading **Single Dot** or **Double Dots** is replaced by the CWD. The method returns whether anything has changed.
+
+```rust
+use std::path::PathBuf;
+
+use path_dedot::*;
+
+# #[cfg(not(windows))]
+# {
+let mut path = PathBuf::from("/path/to/../123/./456");
+
+assert!(path.dedot_in_place_from("/").unwrap());
+assert_eq!("/path/123/456", path.to_str().unwrap());
+
+assert!(!path.dedot_in_place_from("/").unwrap());
+# }
+```
+
+### Dedotting many paths
+
+Without a cache feature, every `parse_dot` call which needs the CWD fetches it again. A `Dedotter` fetches it at most once, and only when a path starts with a **Single Dot** or **Double Dots**, which the `needs_cwd` method tells. Each path gets its own result.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# #[cfg(not(windows))]
+# {
+let mut dedotter = Dedotter::new();
+
+let results: Vec<_> = dedotter.parse_dot_all(["/path/to/../123", "/path/./456"]).collect();
+
+assert_eq!(Path::new("/path/123"), results[0].as_ref().unwrap());
+assert_eq!(Path::new("/path/456"), results[1].as_ref().unwrap());
+assert!(!dedotter.has_cwd());
+
+let mut dedotter = Dedotter::new().cwd("/cwd");
+
+assert_eq!(Path::new("/cwd/path"), dedotter.parse_dot("./path").unwrap());
+# }
+```
+
+### Checking whether a path is already dedotted
+
+With the `is_dedotted` method, you can tell whether the `parse_dot` method would return the path as it is, without fetching the CWD or allocating anything.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# #[cfg(not(windows))]
+# {
+assert!(Path::new("/path/to/123").is_dedotted());
+assert!(!Path::new("/path/to/../123").is_dedotted());
+assert!(!Path::new("/path//to/123/").is_dedotted());
+# }
+```
+
+### Cleaning without the current working directory
+
+With the `clean` method, the path is parsed purely lexically. It never touches the CWD and cannot fail. A **Single Dot** is removed and a leading **Double Dots** of a relative path is kept instead of being resolved.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# if cfg!(unix) {
+assert_eq!("../123/456", Path::new("./path/../../123/456/./777/..").clean().to_str().unwrap());
+assert_eq!("/123/456", Path::new("/path/../../123/456/./777/..").clean().to_str().unwrap());
+assert_eq!(".", Path::new("path/..").clean().to_str().unwrap());
+# }
+```
+
+### Dedotting Windows paths on any host
+
+The `windows` module parses prefixes, separators and dots with the Windows syntax on any host. Its functions work on `str`, `OsStr` or `[u8]` and give the same results as the `parse_dot_from` and `clean` methods on Windows.
+
+```rust
+use path_dedot::windows;
+
+assert_eq!(r"C:\path\to\777", windows::parse_dot_from(r"C:\path\to\123\..\777", r"C:\").unwrap());
+assert_eq!(r"D:\cwd\123", windows::parse_dot_from(r"..\123", r"D:\cwd\sub").unwrap());
+assert_eq!(r"\\server\share\123", windows::clean(r"\\server\share\path\..\..\123"));
+```
+
+### Dedotting POSIX paths on any host
+
+Likewise, the `posix` module treats `/` as the only separator on any host, and gives the same results as the `parse_dot_from` and `clean` methods on targets other than Windows, including WebAssembly.
+
+```rust
+use path_dedot::posix;
+
+assert_eq!("/srv/app/logs", posix::parse_dot_from("../logs", "/srv/app/bin").unwrap());
+assert_eq!(r"/srv/C:\logs", posix::parse_dot_from(r"./C:\logs", "/srv").unwrap());
+assert_eq!(b"../logs".as_slice(), &*posix::clean(b"bin/../../logs".as_slice()));
+```
+
+### Removing dot segments from URL paths
+
+The path of a URL has its own rules: a trailing `/` is kept after a dot segment, `%2e` is also a dot, and `;params`, queries and fragments are not touched. The `url::remove_dot_segments` function follows RFC 3986 §5.2.4.
+
+```rust
+use path_dedot::url;
+
+assert_eq!("/a/g", url::remove_dot_segments("/a/b/c/./../../g"));
+assert_eq!("/a/?next=../b", url::remove_dot_segments("/a/b/%2e%2e?next=../b"));
+```
+
+### Converting between paths and `file:` URLs
+
+The `url::from_file_url` function percent-decodes a `file:` URL into a native path without dots, and the `url::to_file_url` function does the opposite. On Windows, drive letters and hosts are mapped to the `Disk` and `UNC` prefixes, e.g. `file:///C:/a/../b` becomes `C:\b` and `file://server/share/x` becomes `\\server\share\x`. Malformed URLs return a `FileUrlError`.
+
+```rust
+use std::path::Path;
+
+use path_dedot::url;
+
+# #[cfg(not(windows))]
+# {
+assert_eq!(Path::new("/srv/my docs"), url::from_file_url("file:///srv/app/../my%20docs").unwrap());
+assert_eq!("file:///srv/my%20docs", url::to_file_url(Path::new("/srv/my docs")).unwrap());
+# }
+```
+
+## Caching
+
+By default, the `parse_dot` method creates a new `PathBuf` instance of the CWD every time a path starts with a **Single Dot** or **Double Dots**. The overhead is obvious. Although it allows us to safely change the CWD at runtime by the program itself (e.g. using the `std::env::set_current_dir` function) or outside controls (e.g. using gdb to call `chdir`), we don't need that in most cases.
+
+In order to parse paths with better performance, the CWD can be cached with `CwdStrategy::Cache`. The CWD is fetched when it is needed for the first time, and reading it afterwards only takes a read lock and clones an `Arc`. The `update_cwd` function refreshes it after the CWD is changed, and it is safe to call while other threads are dedotting paths, which see either the previous CWD or the new one.
+
+If the CWD cannot be fetched, e.g. it has been deleted, `parse_dot` returns an error instead of panicking, and the CWD stays uncached. The `init_cwd` function caches the CWD up front and reports such an error early, and the `is_cwd_cached` function tells whether it has been cached.
+
+```rust
+use std::path::Path;
+
+use path_dedot::*;
+
+# let strategy = cwd_strategy();
+#
+set_cwd_strategy(CwdStrategy::Cache);
 
 let p = Path::new("./path/to/123/456");
 
@@ -225,123 +470,220 @@ println!("{}", p.parse_dot().unwrap().to_str().unwrap());
 
 std::env::set_current_dir("/").unwrap();
 
-# #[cfg(feature = "unsafe_cache")]
-unsafe {
-    update_cwd();
-}
+update_cwd().unwrap();
 
 println!("{}", p.parse_dot().unwrap().to_str().unwrap());
+#
+# set_cwd_strategy(strategy);
 ```
 
-## Benchmark
+The strategy is chosen for the whole process at runtime. Enabling any of the `once_cell_cache`, `lazy_static_cache` and `unsafe_cache` features only makes `CwdStrategy::Cache` the default. They can be enabled together, e.g. by different crates in the same dependency graph, and the program can still choose the strategy with the `set_cwd_strategy` function.
 
-#### No-cache
+Because the default is shared by the whole process, a cache feature enabled by any crate in the dependency graph makes `parse_dot` in every other crate stop seeing a changed CWD until `update_cwd` is called. Libraries should not enable the cache features. A library which needs the CWD of the moment should use the `parse_dot_with_provider` method with `ProcessCwd`, which always fetches it whatever the strategy is, and leave the choice of the strategy to the program.
 
-```bash
-cargo bench
+```toml
+[dependencies.path-dedot]
+version = "*"
+features = ["once_cell_cache"]
 ```
 
-#### once_cell_cache
+## Migrating from 3.x
 
-```bash
-cargo bench --features once_cell_cache
+Version 4 changes how the cached CWD is accessed, and extends the traits.
+
+* The `update_cwd` function is no longer `unsafe`, and it returns an `io::Result<()>` because the CWD may not be fetchable.
+* The `CWD` static is not public anymore. The cached CWD is read with `CachedCwd`, which returns a `CwdPath` that derefs to a `Path`.
+* The cache features do not choose different implementations anymore. Any of them makes `CwdStrategy::Cache` the default, and the `set_cwd_strategy` function can choose it at runtime. Like in 3.x, the default applies to the whole process, so a feature enabled by one crate affects every `parse_dot` call. Libraries should use `ProcessCwd` with the `parse_dot_with_provider` method if they rely on the CWD of the moment. The `lazy_static` dependency is removed, and the `lazy_static` feature which enabled it is a deprecated no-op.
+* The `ParseDot` trait has more required methods, so types outside this crate which implement it need to implement them as well. The methods which touch the file system are in the `ParseDotFs` trait.
+* The errors of the `ParseDot` methods are `DedotError`s wrapped in `io::Error`s, which can be matched by the `DedotError::downcast_ref` function instead of by their messages.
+
+```rust
+use path_dedot::*;
+
+// 3.x: unsafe { update_cwd(); }
+update_cwd().unwrap();
+
+// 3.x: CWD.as_path()
+let cwd = CachedCwd.cwd().unwrap();
+
+assert!(cwd.is_absolute());
 ```
 
-#### lazy_static_cache
+## Minimum Supported Rust Version
+
+The MSRV is **1.74**. It was raised from 1.60 because the host-independent `posix` and `windows` modules work on the bytes of any `OsStr` through `OsStr::as_encoded_bytes` and `OsStr::from_encoded_bytes_unchecked`, which were stabilized in Rust 1.74. Before that, only Unix exposed the bytes of a path, so Windows paths which are not valid UTF-8 could not be dedotted.
+
+## Benchmark
+
+#### No-cache
 
 ```bash
-cargo bench --features lazy_static_cache
+cargo bench
 ```
 
-#### unsafe_cache
+#### Cache
 
 ```bash
-cargo bench --features unsafe_cache
+cargo bench --features once_cell_cache
 ```
 
 */
 
-#[cfg(any(
-    all(feature = "lazy_static_cache", feature = "unsafe_cache"),
-    all(feature = "once_cell_cache", feature = "unsafe_cache"),
-    all(feature = "lazy_static_cache", feature = "once_cell_cache")
-))]
-compile_error!("You can only enable at most one caching mechanism for `path-dedot`.");
-
-#[cfg(feature = "lazy_static_cache")]
-#[macro_use]
-extern crate lazy_static;
-
-#[cfg(not(feature = "lazy_static_cache"))]
 extern crate once_cell;
 
 use std::{
     borrow::Cow,
     ffi::OsString,
-    io,
+    io, mem,
     path::{self, Path, PathBuf},
 };
 
+mod clean;
+mod confine;
+mod cwd;
+mod dedot_components;
+mod dedotter;
+mod env_var;
+mod error;
+mod escape;
+mod native;
+mod options;
 mod parse_dot;
-
-#[macro_use]
-mod macros;
-
-#[cfg(any(unix, all(target_family = "wasm", feature = "use_unix_paths_on_wasm")))]
-mod unix;
-
-#[cfg(windows)]
-mod windows;
-
-#[cfg(feature = "unsafe_cache")]
-mod unsafe_cwd;
-
-#[cfg(not(feature = "lazy_static_cache"))]
+mod parse_dot_fs;
+mod parse_dot_mut;
+mod path_str;
+mod physical;
+mod relative;
+mod symlink;
+mod tilde;
+mod tokens;
+pub mod url;
+
+pub mod posix;
+pub mod windows;
+
+pub use clean::*;
+pub use confine::*;
+pub use cwd::{
+    cwd_strategy, init_cwd, is_cwd_cached, set_cwd_strategy, update_cwd, CachedCwd, CwdPath,
+    CwdProvider, CwdStrategy, DefaultCwd, ProcessCwd,
+};
+pub use dedot_components::DedotComponents;
+pub use dedotter::*;
+pub use env_var::*;
+pub use error::*;
+pub use escape::*;
 use once_cell::sync::Lazy;
+pub use options::*;
 pub use parse_dot::*;
+pub use parse_dot_fs::*;
+pub use parse_dot_mut::*;
+pub use path_str::PathStr;
+pub use symlink::*;
+pub use tilde::*;
 #[cfg(windows)]
 pub use windows::ParsePrefix;
 
-#[cfg(not(feature = "lazy_static_cache"))]
 /// The main separator for the target OS.
 pub static MAIN_SEPARATOR: Lazy<OsString> =
     Lazy::new(|| OsString::from(path::MAIN_SEPARATOR.to_string()));
 
-#[cfg(feature = "lazy_static_cache")]
-lazy_static! {
-    /// Current working directory.
-    pub static ref MAIN_SEPARATOR: OsString = OsString::from(path::MAIN_SEPARATOR.to_string());
-}
-
 impl ParseDot for PathBuf {
     #[inline]
-    fn parse_dot(&self) -> io::Result<Cow<Path>> {
+    fn parse_dot(&self) -> io::Result<Cow<'_, Path>> {
         self.as_path().parse_dot()
     }
 
     #[inline]
-    fn parse_dot_from(&self, cwd: impl AsRef<Path>) -> io::Result<Cow<Path>> {
+    fn parse_dot_from(&self, cwd: impl AsRef<Path>) -> io::Result<Cow<'_, Path>> {
         self.as_path().parse_dot_from(cwd)
     }
-}
 
-#[cfg(feature = "once_cell_cache")]
-/// Current working directory.
-pub static CWD: Lazy<PathBuf> = Lazy::new(|| std::env::current_dir().unwrap());
+    #[inline]
+    fn parse_dot_with(&self, options: DedotOptions) -> io::Result<Cow<'_, Path>> {
+        self.as_path().parse_dot_with(options)
+    }
 
-#[cfg(feature = "lazy_static_cache")]
-lazy_static! {
-    /// Current working directory.
-    pub static ref CWD: PathBuf = std::env::current_dir().unwrap();
+    #[inline]
+    fn parse_dot_from_with(
+        &self,
+        cwd: impl AsRef<Path>,
+        options: DedotOptions,
+    ) -> io::Result<Cow<'_, Path>> {
+        self.as_path().parse_dot_from_with(cwd, options)
+    }
+
+    #[inline]
+    fn parse_dot_with_provider(
+        &self,
+        provider: &(impl CwdProvider + ?Sized),
+        options: DedotOptions,
+    ) -> io::Result<Cow<'_, Path>> {
+        self.as_path().parse_dot_with_provider(provider, options)
+    }
+
+    #[inline]
+    fn relative_to(&self, base: impl AsRef<Path>, cwd: impl AsRef<Path>) -> io::Result<PathBuf> {
+        self.as_path().relative_to(base, cwd)
+    }
+
+    #[inline]
+    fn dedot_components<'a>(&'a self, cwd: &'a Path) -> io::Result<DedotComponents<'a>> {
+        self.as_path().dedot_components(cwd)
+    }
+
+    #[inline]
+    fn is_dedotted(&self) -> bool {
+        self.as_path().is_dedotted()
+    }
+
+    #[inline]
+    fn needs_cwd(&self) -> bool {
+        self.as_path().needs_cwd()
+    }
+}
+
+impl Clean for PathBuf {
+    #[inline]
+    fn clean(&self) -> Cow<'_, Path> {
+        self.as_path().clean()
+    }
 }
 
-#[cfg(feature = "unsafe_cache")]
-/// Current working directory.
-pub static mut CWD: unsafe_cwd::UnsafeCWD = unsafe_cwd::UnsafeCWD::new();
+impl ParseDotMut for OsString {
+    #[inline]
+    fn dedot_in_place(&mut self) -> io::Result<bool> {
+        let mut path = PathBuf::from(mem::take(self));
+
+        let result = path.dedot_in_place();
+
+        *self = path.into_os_string();
+
+        result
+    }
+
+    #[inline]
+    fn dedot_in_place_from(&mut self, cwd: impl AsRef<Path>) -> io::Result<bool> {
+        let mut path = PathBuf::from(mem::take(self));
+
+        let result = path.dedot_in_place_from(cwd);
+
+        *self = path.into_os_string();
 
-#[cfg(feature = "unsafe_cache")]
-/// Initialize or update the CWD cached in the `path-dedot` crate after using the `std::env::set_current_dir` function. It is not a safe operation. Make sure there is no `parse_dot` method running at this moment.
-#[allow(clippy::missing_safety_doc)]
-pub unsafe fn update_cwd() {
-    CWD.update();
+        result
+    }
+
+    #[inline]
+    fn dedot_in_place_with_provider(
+        &mut self,
+        provider: &(impl CwdProvider + ?Sized),
+    ) -> io::Result<bool> {
+        let mut path = PathBuf::from(mem::take(self));
+
+        let result = path.dedot_in_place_with_provider(provider);
+
+        *self = path.into_os_string();
+
+        result
+    }
 }
@@ -0,0 +1,151 @@
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+};
+
+// the implementations below are built on the module with the path syntax of the target OS
+#[cfg(not(windows))]
+use crate::posix as imp;
+#[cfg(windows)]
+use crate::windows as imp;
+use crate::{
+    cwd,
+    path_str::{to_path_cow, with_path_buffer},
+    relative, tilde,
+    tokens::TokenVec,
+    Clean, CwdProvider, DedotComponents, DedotOptions, DefaultCwd, EscapePolicy, ParseDot,
+    ParseDotMut,
+};
+
+impl ParseDot for Path {
+    #[inline]
+    fn parse_dot(&self) -> io::Result<Cow<'_, Path>> {
+        self.parse_dot_with(DedotOptions::new())
+    }
+
+    #[inline]
+    fn parse_dot_from(&self, cwd: impl AsRef<Path>) -> io::Result<Cow<'_, Path>> {
+        self.parse_dot_from_with(cwd, DedotOptions::new())
+    }
+
+    #[inline]
+    fn parse_dot_with(&self, options: DedotOptions) -> io::Result<Cow<'_, Path>> {
+        self.parse_dot_with_provider(&DefaultCwd, options)
+    }
+
+    #[inline]
+    fn parse_dot_from_with(
+        &self,
+        cwd: impl AsRef<Path>,
+        options: DedotOptions,
+    ) -> io::Result<Cow<'_, Path>> {
+        parse_dot_lazy(self, options, || Ok(cwd))
+    }
+
+    #[inline]
+    fn parse_dot_with_provider(
+        &self,
+        provider: &(impl CwdProvider + ?Sized),
+        options: DedotOptions,
+    ) -> io::Result<Cow<'_, Path>> {
+        // the CWD is only fetched if it is used, so absolute paths never fail because of it
+        parse_dot_lazy(self, options, || cwd::provide_cwd(provider, self))
+    }
+
+    #[inline]
+    fn relative_to(&self, base: impl AsRef<Path>, cwd: impl AsRef<Path>) -> io::Result<PathBuf> {
+        relative::relative_to(self, base.as_ref(), cwd.as_ref())
+    }
+
+    #[inline]
+    fn dedot_components<'a>(&'a self, cwd: &'a Path) -> io::Result<DedotComponents<'a>> {
+        let mut tokens = TokenVec::default();
+
+        imp::dedot(
+            self.as_os_str().as_encoded_bytes(),
+            cwd.as_os_str().as_encoded_bytes(),
+            EscapePolicy::default(),
+            &|| self.to_path_buf(),
+            &mut tokens,
+        )?;
+
+        Ok(DedotComponents::new(tokens))
+    }
+
+    #[inline]
+    fn is_dedotted(&self) -> bool {
+        imp::is_dedotted_bytes(self.as_os_str().as_encoded_bytes())
+    }
+
+    #[inline]
+    fn needs_cwd(&self) -> bool {
+        imp::needs_cwd_bytes(self.as_os_str().as_encoded_bytes())
+    }
+}
+
+/// Remove dots in the path like the `parse_dot_from_with` method, but `get_cwd` is only called if the current working directory is used. The tilde is expanded once, before checking whether the expanded path needs the current working directory, because the home directory can start with dots as well.
+pub(crate) fn parse_dot_lazy<C: AsRef<Path>>(
+    path: &Path,
+    options: DedotOptions,
+    get_cwd: impl FnOnce() -> io::Result<C>,
+) -> io::Result<Cow<'_, Path>> {
+    if options.expand_tilde {
+        if let Cow::Owned(mut expanded) = tilde::expand_tilde_option(path)? {
+            if options.keep_trailing_separator
+                && imp::ends_with_directory(path.as_os_str().as_encoded_bytes())
+            {
+                expanded.push("");
+            }
+
+            return parse_dot_lazy(&expanded, options.expand_tilde(false), get_cwd)
+                .map(|path| Cow::from(path.into_owned()));
+        }
+    }
+
+    let cwd = if path.needs_cwd() { Some(get_cwd()?) } else { None };
+
+    let dedotted = imp::parse_dot_bytes(
+        path.as_os_str().as_encoded_bytes(),
+        cwd.as_ref().map(|cwd| cwd.as_ref().as_os_str().as_encoded_bytes()).unwrap_or_default(),
+        options,
+        &|| path.to_path_buf(),
+    )?;
+
+    Ok(to_path_cow(path, dedotted))
+}
+
+impl ParseDotMut for PathBuf {
+    #[inline]
+    fn dedot_in_place(&mut self) -> io::Result<bool> {
+        self.dedot_in_place_with_provider(&DefaultCwd)
+    }
+
+    #[inline]
+    fn dedot_in_place_from(&mut self, cwd: impl AsRef<Path>) -> io::Result<bool> {
+        let cwd = cwd.as_ref().as_os_str().as_encoded_bytes();
+
+        with_path_buffer(self, |buffer| imp::dedot_in_place_bytes(buffer, cwd))
+    }
+
+    #[inline]
+    fn dedot_in_place_with_provider(
+        &mut self,
+        provider: &(impl CwdProvider + ?Sized),
+    ) -> io::Result<bool> {
+        if self.needs_cwd() {
+            let cwd = cwd::provide_cwd(provider, self)?;
+
+            self.dedot_in_place_from(cwd)
+        } else {
+            self.dedot_in_place_from("")
+        }
+    }
+}
+
+impl Clean for Path {
+    #[inline]
+    fn clean(&self) -> Cow<'_, Path> {
+        to_path_cow(self, imp::clean_bytes(self.as_os_str().as_encoded_bytes()))
+    }
+}
@@ -0,0 +1,51 @@
+use crate::EscapePolicy;
+
+/// Options for the `parse_dot_with` and `parse_dot_from_with` methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DedotOptions {
+    /// How to handle **Double Dots** which climb above the start of the path.
+    pub escape_policy:           EscapePolicy,
+    /// Whether to expand a leading `~` or `~user` to the home directory before removing dots. See the `expand_tilde` function.
+    pub expand_tilde:            bool,
+    /// Whether to keep a trailing separator if the path ends with a separator, a **Single Dot** or **Double Dots**, e.g. `dir/sub/..` becomes `dir/` instead of `dir`.
+    pub keep_trailing_separator: bool,
+}
+
+impl DedotOptions {
+    /// Create the options which behave the same as the `parse_dot` method.
+    #[inline]
+    pub fn new() -> DedotOptions {
+        DedotOptions::default()
+    }
+
+    /// Set how to handle **Double Dots** which climb above the start of the path.
+    #[inline]
+    pub fn escape_policy(mut self, escape_policy: EscapePolicy) -> DedotOptions {
+        self.escape_policy = escape_policy;
+
+        self
+    }
+
+    /// Set whether to expand a leading `~` or `~user` to the home directory before removing dots.
+    #[inline]
+    pub fn expand_tilde(mut self, expand_tilde: bool) -> DedotOptions {
+        self.expand_tilde = expand_tilde;
+
+        self
+    }
+
+    /// Set whether to keep a trailing separator if the path ends with a separator, a **Single Dot** or **Double Dots**.
+    #[inline]
+    pub fn keep_trailing_separator(mut self, keep_trailing_separator: bool) -> DedotOptions {
+        self.keep_trailing_separator = keep_trailing_separator;
+
+        self
+    }
+}
+
+impl From<EscapePolicy> for DedotOptions {
+    #[inline]
+    fn from(escape_policy: EscapePolicy) -> Self {
+        DedotOptions::new().escape_policy(escape_policy)
+    }
+}
@@ -1,10 +1,47 @@
-use std::{borrow::Cow, io, path::Path};
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{CwdProvider, DedotComponents, DedotOptions};
 
 /// Let `Path` and `PathBuf` have `parse_dot` method.
 pub trait ParseDot {
     /// Remove dots in the path and create a new `PathBuf` instance on demand.
-    fn parse_dot(&self) -> io::Result<Cow<Path>>;
+    fn parse_dot(&self) -> io::Result<Cow<'_, Path>>;
 
     /// Remove dots in the path and create a new `PathBuf` instance on demand. It gets the current working directory as the second argument.
-    fn parse_dot_from(&self, cwd: impl AsRef<Path>) -> io::Result<Cow<Path>>;
+    fn parse_dot_from(&self, cwd: impl AsRef<Path>) -> io::Result<Cow<'_, Path>>;
+
+    /// Remove dots in the path with the given options and create a new `PathBuf` instance on demand.
+    fn parse_dot_with(&self, options: DedotOptions) -> io::Result<Cow<'_, Path>>;
+
+    /// Remove dots in the path with the given options and create a new `PathBuf` instance on demand. It gets the current working directory as the second argument.
+    fn parse_dot_from_with(
+        &self,
+        cwd: impl AsRef<Path>,
+        options: DedotOptions,
+    ) -> io::Result<Cow<'_, Path>>;
+
+    /// Remove dots in the path with the given options and create a new `PathBuf` instance on demand. The current working directory is got from `provider`, only if the path starts with a **Single Dot** or **Double Dots**.
+    fn parse_dot_with_provider(
+        &self,
+        provider: &(impl CwdProvider + ?Sized),
+        options: DedotOptions,
+    ) -> io::Result<Cow<'_, Path>>;
+
+    /// Get the path, which is dedotted with the current working directory given as the second argument, as seen from `base`. `base` is dedotted in the same way. A **Single Dot** is returned if both paths are the same.
+    ///
+    /// An error is returned if the two paths have different prefixes (e.g. different drive letters) or roots.
+    fn relative_to(&self, base: impl AsRef<Path>, cwd: impl AsRef<Path>) -> io::Result<PathBuf>;
+
+    /// Remove dots in the path and iterate over the components of the result, which is never joined into a new `PathBuf`. It gets the current working directory as the second argument, whose components are spliced in for a leading **Single Dot** or **Double Dots**.
+    fn dedot_components<'a>(&'a self, cwd: &'a Path) -> io::Result<DedotComponents<'a>>;
+
+    /// Check whether the path is already dedotted, which means the `parse_dot` method returns it as it is (`Cow::Borrowed`). The current working directory is not needed, and nothing is allocated.
+    fn is_dedotted(&self) -> bool;
+
+    /// Check whether the `parse_dot` method needs the current working directory, which is only the case when the path starts with a **Single Dot** or **Double Dots**. A leading `~` is not expanded.
+    fn needs_cwd(&self) -> bool;
 }
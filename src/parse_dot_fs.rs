@@ -0,0 +1,101 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{cwd, physical, symlink, DefaultCwd, ParseDot, SymlinkAwarePath, SymlinkPolicy};
+
+/// Let `Path` and `PathBuf` have the methods which remove dots by looking at the file system. Unlike the `ParseDot` trait, they are not purely lexical.
+pub trait ParseDotFs {
+    /// Canonicalize the longest existing prefix of the path and remove dots in the rest of the path. Unlike the `parse_dot` method, symbolic links followed by **Double Dots** are resolved as the operating system does, and unlike the `std::fs::canonicalize` function, the path does not need to exist.
+    ///
+    /// The result follows the platform. On Unix, **Double Dots** after an existing file which is not a directory, e.g. `file/..`, is an `ENOTDIR` error, like the kernel. On Windows, `file\..` is removed lexically, as Windows itself does, and the result is a verbatim path (e.g. `\\?\C:\path`) because the existing prefix is canonicalized by `std::fs::canonicalize`.
+    fn parse_dot_physical(&self) -> io::Result<PathBuf>;
+
+    /// Canonicalize the longest existing prefix of the path and remove dots in the rest of the path. It gets the current working directory as the second argument, which must be absolute if the path is relative. The result follows the platform in the same way as `parse_dot_physical`.
+    fn parse_dot_physical_from(&self, cwd: impl AsRef<Path>) -> io::Result<PathBuf>;
+
+    /// Remove dots in the path, but check each component which is about to be removed by **Double Dots** with `std::fs::symlink_metadata`. If the component is a symbolic link, the **Double Dots** is kept or an error is returned, according to `symlink_policy`. The checked components are reported in the result.
+    fn parse_dot_symlink_aware(
+        &self,
+        symlink_policy: SymlinkPolicy,
+    ) -> io::Result<SymlinkAwarePath>;
+
+    /// Remove dots in the path, but check each component which is about to be removed by **Double Dots** with `std::fs::symlink_metadata`. It gets the current working directory as the second argument.
+    fn parse_dot_from_symlink_aware(
+        &self,
+        cwd: impl AsRef<Path>,
+        symlink_policy: SymlinkPolicy,
+    ) -> io::Result<SymlinkAwarePath>;
+}
+
+impl ParseDotFs for Path {
+    #[inline]
+    fn parse_dot_physical(&self) -> io::Result<PathBuf> {
+        // the CWD is only fetched if it is joined, so absolute paths never fail because of it
+        if self.is_absolute() {
+            self.parse_dot_physical_from("")
+        } else {
+            let cwd = cwd::provide_cwd(&DefaultCwd, self)?;
+
+            self.parse_dot_physical_from(cwd)
+        }
+    }
+
+    #[inline]
+    fn parse_dot_physical_from(&self, cwd: impl AsRef<Path>) -> io::Result<PathBuf> {
+        physical::parse_dot_physical_from(self, cwd.as_ref())
+    }
+
+    #[inline]
+    fn parse_dot_symlink_aware(
+        &self,
+        symlink_policy: SymlinkPolicy,
+    ) -> io::Result<SymlinkAwarePath> {
+        if self.needs_cwd() {
+            let cwd = cwd::provide_cwd(&DefaultCwd, self)?;
+
+            self.parse_dot_from_symlink_aware(cwd, symlink_policy)
+        } else {
+            self.parse_dot_from_symlink_aware("", symlink_policy)
+        }
+    }
+
+    #[inline]
+    fn parse_dot_from_symlink_aware(
+        &self,
+        cwd: impl AsRef<Path>,
+        symlink_policy: SymlinkPolicy,
+    ) -> io::Result<SymlinkAwarePath> {
+        symlink::parse_dot_from_symlink_aware(self, cwd.as_ref(), symlink_policy)
+    }
+}
+
+impl ParseDotFs for PathBuf {
+    #[inline]
+    fn parse_dot_physical(&self) -> io::Result<PathBuf> {
+        self.as_path().parse_dot_physical()
+    }
+
+    #[inline]
+    fn parse_dot_physical_from(&self, cwd: impl AsRef<Path>) -> io::Result<PathBuf> {
+        self.as_path().parse_dot_physical_from(cwd)
+    }
+
+    #[inline]
+    fn parse_dot_symlink_aware(
+        &self,
+        symlink_policy: SymlinkPolicy,
+    ) -> io::Result<SymlinkAwarePath> {
+        self.as_path().parse_dot_symlink_aware(symlink_policy)
+    }
+
+    #[inline]
+    fn parse_dot_from_symlink_aware(
+        &self,
+        cwd: impl AsRef<Path>,
+        symlink_policy: SymlinkPolicy,
+    ) -> io::Result<SymlinkAwarePath> {
+        self.as_path().parse_dot_from_symlink_aware(cwd, symlink_policy)
+    }
+}
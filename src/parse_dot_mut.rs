@@ -0,0 +1,18 @@
+use std::{io, path::Path};
+
+use crate::CwdProvider;
+
+/// Let `PathBuf` and `OsString` have `dedot_in_place` method.
+pub trait ParseDotMut {
+    /// Remove dots in the path by rewriting its own buffer, which gives the same result as the `parse_dot` method. Bytes are shifted and truncated, and the buffer only grows when a leading **Single Dot** or **Double Dots** is replaced by the current working directory. Return whether the path is changed.
+    fn dedot_in_place(&mut self) -> io::Result<bool>;
+
+    /// Remove dots in the path by rewriting its own buffer. It gets the current working directory as the second argument.
+    fn dedot_in_place_from(&mut self, cwd: impl AsRef<Path>) -> io::Result<bool>;
+
+    /// Remove dots in the path by rewriting its own buffer. The current working directory is got from `provider`, only if the path starts with a **Single Dot** or **Double Dots**.
+    fn dedot_in_place_with_provider(
+        &mut self,
+        provider: &(impl CwdProvider + ?Sized),
+    ) -> io::Result<bool>;
+}
@@ -0,0 +1,133 @@
+use std::{
+    borrow::Cow,
+    ffi::{OsStr, OsString},
+    mem,
+    path::{Path, PathBuf},
+};
+
+/// A string which can be dedotted with the syntax of another platform, i.e. `str`, `OsStr` or `[u8]`.
+pub trait PathStr: private::Sealed {}
+
+impl PathStr for str {}
+
+impl PathStr for OsStr {}
+
+impl PathStr for [u8] {}
+
+pub(crate) mod private {
+    use std::path::PathBuf;
+
+    pub trait Sealed: ToOwned {
+        fn as_bytes(&self) -> &[u8];
+
+        /// Create an owned string from the bytes which are joined from the pieces of strings of this type and ASCII characters.
+        fn from_bytes(bytes: Vec<u8>) -> Self::Owned;
+
+        fn to_path_buf(&self) -> PathBuf;
+    }
+}
+
+impl private::Sealed for str {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        str::as_bytes(self)
+    }
+
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> String {
+        // the strings are only cut next to ASCII characters, so they are still valid UTF-8
+        String::from_utf8(bytes)
+            .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned())
+    }
+
+    #[inline]
+    fn to_path_buf(&self) -> PathBuf {
+        PathBuf::from(self)
+    }
+}
+
+impl private::Sealed for OsStr {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self.as_encoded_bytes()
+    }
+
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> OsString {
+        // SAFETY: the strings are only cut next to ASCII characters
+        unsafe { OsString::from_encoded_bytes_unchecked(bytes) }
+    }
+
+    #[inline]
+    fn to_path_buf(&self) -> PathBuf {
+        PathBuf::from(self)
+    }
+}
+
+impl private::Sealed for [u8] {
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn from_bytes(bytes: Vec<u8>) -> Vec<u8> {
+        bytes
+    }
+
+    #[inline]
+    fn to_path_buf(&self) -> PathBuf {
+        #[cfg(unix)]
+        {
+            PathBuf::from(<OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(self))
+        }
+
+        #[cfg(not(unix))]
+        {
+            PathBuf::from(String::from_utf8_lossy(self).into_owned())
+        }
+    }
+}
+
+/// Convert a slice of the bytes of an `OsStr`, which is cut next to ASCII characters, back to an `OsStr`.
+///
+/// # Safety
+///
+/// `bytes` must come from `OsStr::as_encoded_bytes`.
+#[inline]
+pub(crate) unsafe fn bytes_to_os_str(bytes: &[u8]) -> &OsStr {
+    OsStr::from_encoded_bytes_unchecked(bytes)
+}
+
+/// Convert the result of a byte function, whose `Cow::Borrowed` is always the original path, to the type of the path.
+#[inline]
+pub(crate) fn from_bytes_cow<'a, S: PathStr + ?Sized>(
+    path: &'a S,
+    cow: Cow<'_, [u8]>,
+) -> Cow<'a, S> {
+    match cow {
+        Cow::Borrowed(_) => Cow::Borrowed(path),
+        Cow::Owned(bytes) => Cow::Owned(S::from_bytes(bytes)),
+    }
+}
+
+/// Convert the result of a byte function for a `Path` back to a `Cow<Path>`.
+#[inline]
+pub(crate) fn to_path_cow<'a>(path: &'a Path, cow: Cow<'_, [u8]>) -> Cow<'a, Path> {
+    match from_bytes_cow(path.as_os_str(), cow) {
+        Cow::Borrowed(path) => Cow::from(Path::new(path)),
+        Cow::Owned(path) => Cow::from(PathBuf::from(path)),
+    }
+}
+
+/// Run a byte function on the buffer of a `PathBuf`, whose allocation is kept. The function may only cut the bytes next to ASCII characters and join them with ASCII characters or the bytes of other paths.
+#[inline]
+pub(crate) fn with_path_buffer<T>(path: &mut PathBuf, f: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+    let mut buffer = mem::take(path).into_os_string().into_encoded_bytes();
+
+    let result = f(&mut buffer);
+
+    *path = PathBuf::from(<OsStr as private::Sealed>::from_bytes(buffer));
+
+    result
+}
@@ -0,0 +1,90 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::DedotError;
+
+/// Canonicalize the longest existing prefix of `path`, which is joined to `cwd` if it is relative, and lexically apply the rest of the components to it.
+///
+/// A relative `cwd` is refused, because the process CWD would be used to resolve it silently.
+pub(crate) fn parse_dot_physical_from(path: &Path, cwd: &Path) -> io::Result<PathBuf> {
+    if path.as_os_str().is_empty() {
+        return Ok(PathBuf::new());
+    }
+
+    let joined = if path.is_absolute() { path.to_path_buf() } else { cwd.join(path) };
+
+    if !joined.has_root() {
+        return Err(DedotError::RelativeCwd {
+            path: path.to_path_buf(), cwd: cwd.to_path_buf()
+        }
+        .into());
+    }
+
+    let mut iter = joined.components().peekable();
+
+    let mut resolved = PathBuf::new();
+
+    while let Some(component) = iter.peek() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                resolved.push(component.as_os_str());
+
+                iter.next();
+            },
+            _ => break,
+        }
+    }
+
+    if !resolved.as_os_str().is_empty() {
+        resolved = fs::canonicalize(resolved)?;
+    }
+
+    // the number of the components at the end of `resolved` which do not exist
+    let mut lexical_length = 0usize;
+
+    for component in iter {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if lexical_length > 0 {
+                    resolved.pop();
+
+                    lexical_length -= 1;
+                } else {
+                    // like the kernel, `file/..` is not the parent directory of `file`
+                    #[cfg(unix)]
+                    if !resolved.is_dir() {
+                        return Err(io::Error::from_raw_os_error(libc::ENOTDIR));
+                    }
+
+                    // the existing part has been canonicalized, so there is no symbolic link in it and it is safe to pop
+                    resolved.pop();
+                }
+            },
+            _ => {
+                if lexical_length > 0 {
+                    resolved.push(component.as_os_str());
+
+                    lexical_length += 1;
+                } else {
+                    let candidate = resolved.join(component.as_os_str());
+
+                    match fs::canonicalize(&candidate) {
+                        Ok(canonical) => resolved = canonical,
+                        Err(error) if error.kind() == ErrorKind::NotFound => {
+                            resolved = candidate;
+
+                            lexical_length = 1;
+                        },
+                        Err(error) => return Err(error),
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(resolved)
+}
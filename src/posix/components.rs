@@ -0,0 +1,114 @@
+/// A component of a POSIX path, which is parsed in the same way as `std::path::Component` on Unix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Component<'a> {
+    RootDir,
+    CurDir,
+    ParentDir,
+    Normal(&'a [u8]),
+}
+
+impl<'a> Component<'a> {
+    #[inline]
+    pub(crate) fn as_bytes(self) -> &'a [u8] {
+        match self {
+            Component::RootDir => b"/",
+            Component::CurDir => b".",
+            Component::ParentDir => b"..",
+            Component::Normal(token) => token,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    StartDir,
+    Body,
+    Done,
+}
+
+/// An iterator over the components of a POSIX path, which behaves the same as `std::path::Components` on Unix.
+#[derive(Debug, Clone)]
+pub(crate) struct Components<'a> {
+    path:  &'a [u8],
+    state: State,
+}
+
+impl<'a> Components<'a> {
+    #[inline]
+    pub(crate) fn new(path: &'a [u8]) -> Components<'a> {
+        Components {
+            path,
+            state: State::StartDir,
+        }
+    }
+}
+
+impl<'a> Iterator for Components<'a> {
+    type Item = Component<'a>;
+
+    fn next(&mut self) -> Option<Component<'a>> {
+        loop {
+            match self.state {
+                State::StartDir => {
+                    self.state = State::Body;
+
+                    match self.path {
+                        [b'/', ..] => {
+                            self.path = &self.path[1..];
+
+                            return Some(Component::RootDir);
+                        },
+                        // a leading Single Dot is kept as a component
+                        [b'.'] | [b'.', b'/', ..] => {
+                            self.path = &self.path[1..];
+
+                            return Some(Component::CurDir);
+                        },
+                        _ => (),
+                    }
+                },
+                State::Body => {
+                    if self.path.is_empty() {
+                        self.state = State::Done;
+
+                        continue;
+                    }
+
+                    let (token, size) = match self.path.iter().position(|&b| b == b'/') {
+                        Some(index) => (&self.path[..index], index + 1),
+                        None => (self.path, self.path.len()),
+                    };
+
+                    self.path = &self.path[size..];
+
+                    match token {
+                        b"." | b"" => (),
+                        b".." => return Some(Component::ParentDir),
+                        _ => return Some(Component::Normal(token)),
+                    }
+                },
+                State::Done => return None,
+            }
+        }
+    }
+}
+
+/// The components of the parent of a POSIX path, like `Path::parent` on Unix.
+pub(crate) fn parent(path: &[u8]) -> Option<impl Iterator<Item = Component<'_>>> {
+    let count = Components::new(path).count();
+
+    match Components::new(path).last() {
+        Some(Component::Normal(_)) | Some(Component::CurDir) | Some(Component::ParentDir) => {
+            Some(Components::new(path).take(count - 1))
+        },
+        _ => None,
+    }
+}
+
+/// Whether a POSIX path is just a root, like `path == Path::new("/")` on Unix.
+#[inline]
+pub(crate) fn is_root(path: &[u8]) -> bool {
+    let mut iter = Components::new(path);
+
+    iter.next() == Some(Component::RootDir) && iter.next().is_none()
+}
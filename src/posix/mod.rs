@@ -0,0 +1,354 @@
+/*!
+Dedot paths with the POSIX syntax on any host.
+
+The functions in this module work on `str`, `OsStr` or `[u8]` instead of `Path`, and treat `/` as the only separator, in the same way as `std::path` on Unix. They give the same results as the `ParseDot` and `Clean` implementations on targets other than Windows, which are built on them, so a path headed for a Linux server can be normalized on Windows as well.
+
+The `expand_tilde` option is ignored, because the home directory of the host may not be a POSIX path.
+*/
+
+mod components;
+
+use std::{borrow::Cow, io, path::PathBuf};
+
+use components::{Component, Components};
+
+use crate::{
+    path_str::from_bytes_cow,
+    tokens::{dedot_tail_in_place, JoinedPath, TokenSink},
+    DedotOptions, EscapePolicy, PathStr,
+};
+
+/// The separator of POSIX paths.
+const SEPARATOR: &[u8] = b"/";
+
+/// Make a POSIX path absolute with a given current working directory and remove dots in the path, like the `parse_dot_from` method on Unix.
+///
+/// ```
+/// use path_dedot::posix;
+///
+/// assert_eq!(
+///     "/path/to/777",
+///     posix::parse_dot_from("/path/to/123/../777", "/").unwrap()
+/// );
+/// assert_eq!("/cwd/path", posix::parse_dot_from("./path", "/cwd").unwrap());
+/// // `\` is not a separator
+/// assert_eq!(r"a\..", posix::parse_dot_from(r"a\..", "/cwd").unwrap());
+/// ```
+#[inline]
+pub fn parse_dot_from<'a, S: PathStr + ?Sized>(path: &'a S, cwd: &S) -> io::Result<Cow<'a, S>> {
+    parse_dot_from_with(path, cwd, DedotOptions::new())
+}
+
+/// Make a POSIX path absolute with a given current working directory and remove dots in the path with options, like the `parse_dot_from_with` method on Unix. The `expand_tilde` option is ignored.
+#[inline]
+pub fn parse_dot_from_with<'a, S: PathStr + ?Sized>(
+    path: &'a S,
+    cwd: &S,
+    options: DedotOptions,
+) -> io::Result<Cow<'a, S>> {
+    let dedotted =
+        parse_dot_bytes(path.as_bytes(), cwd.as_bytes(), options, &|| path.to_path_buf())?;
+
+    Ok(from_bytes_cow(path, dedotted))
+}
+
+/// Check whether a POSIX path is already dedotted, which means `parse_dot_from` returns it as it is, like the `is_dedotted` method on Unix. The current working directory is not needed.
+///
+/// ```
+/// use path_dedot::posix;
+///
+/// assert!(posix::is_dedotted("/path/to/123"));
+/// assert!(!posix::is_dedotted("/path/../123"));
+/// assert!(!posix::is_dedotted("/path//123/"));
+/// ```
+#[inline]
+pub fn is_dedotted<S: PathStr + ?Sized>(path: &S) -> bool {
+    is_dedotted_bytes(path.as_bytes())
+}
+
+/// Check whether `parse_dot_from` uses the current working directory for a POSIX path, which is only the case when the path starts with a **Single Dot** or **Double Dots**, like the `needs_cwd` method on Unix.
+///
+/// ```
+/// use path_dedot::posix;
+///
+/// assert!(posix::needs_cwd("./path"));
+/// assert!(posix::needs_cwd("../path"));
+/// assert!(!posix::needs_cwd("/path/../123"));
+/// assert!(!posix::needs_cwd("path/../../123"));
+/// ```
+#[inline]
+pub fn needs_cwd<S: PathStr + ?Sized>(path: &S) -> bool {
+    needs_cwd_bytes(path.as_bytes())
+}
+
+/// Remove dots in the bytes of a POSIX path by rewriting the buffer, like the `dedot_in_place_from` method on Unix. Return whether the path is changed.
+///
+/// ```
+/// use path_dedot::posix;
+///
+/// let mut path = b"/path/to/123/../777".to_vec();
+///
+/// assert!(posix::dedot_in_place_from(&mut path, b"/").unwrap());
+/// assert_eq!(b"/path/to/777", path.as_slice());
+///
+/// let mut path = b"./path".to_vec();
+///
+/// assert!(posix::dedot_in_place_from(&mut path, b"/cwd").unwrap());
+/// assert_eq!(b"/cwd/path", path.as_slice());
+/// ```
+#[inline]
+pub fn dedot_in_place_from(path: &mut Vec<u8>, cwd: &[u8]) -> io::Result<bool> {
+    dedot_in_place_bytes(path, cwd)
+}
+
+/// Lexically remove dots in a POSIX path without the current working directory, like the `clean` method on Unix.
+///
+/// ```
+/// use path_dedot::posix;
+///
+/// assert_eq!("../123", posix::clean("path/to/../../../123"));
+/// assert_eq!("/123", posix::clean("/path/../../123"));
+/// ```
+#[inline]
+pub fn clean<S: PathStr + ?Sized>(path: &S) -> Cow<'_, S> {
+    from_bytes_cow(path, clean_bytes(path.as_bytes()))
+}
+
+/// Push the components of `cwd` into `sink`.
+#[inline]
+fn push_cwd<'a>(sink: &mut impl TokenSink<'a>, cwd: impl Iterator<Item = Component<'a>>) {
+    for component in cwd {
+        match component {
+            Component::RootDir => sink.push_root(),
+            _ => sink.push(component.as_bytes()),
+        }
+    }
+}
+
+/// The implementation of `parse_dot_from_with`. `to_path_buf` creates the path for an `EscapeError`. The result is borrowed only if it is `path` itself.
+pub(crate) fn parse_dot_bytes<'a>(
+    path: &'a [u8],
+    cwd: &[u8],
+    options: DedotOptions,
+    to_path_buf: &dyn Fn() -> PathBuf,
+) -> io::Result<Cow<'a, [u8]>> {
+    let trailing_separator = options.keep_trailing_separator && ends_with_directory(path);
+
+    let mut joined = JoinedPath::new(path, SEPARATOR[0]);
+
+    match dedot(path, cwd, options.escape_policy, to_path_buf, &mut joined)? {
+        Some(has_dots) => Ok(joined.into_cow(has_dots, trailing_separator)),
+        None => Ok(Cow::from(path)),
+    }
+}
+
+/// Remove dots in the path in a single pass, and push the tokens of the result into `sink`. Return whether any component is removed or ignored, or `None` if the path has no components.
+pub(crate) fn dedot<'a>(
+    path: &'a [u8],
+    cwd: &'a [u8],
+    escape_policy: EscapePolicy,
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+) -> io::Result<Option<bool>> {
+    let mut iter = Components::new(path);
+
+    let mut has_dots = false;
+
+    if let Some(first_component) = iter.next() {
+        match first_component {
+            Component::RootDir => sink.push_root(),
+            Component::CurDir => {
+                has_dots = true;
+
+                push_cwd(sink, Components::new(cwd));
+            },
+            Component::ParentDir => {
+                has_dots = true;
+
+                match components::parent(cwd) {
+                    Some(cwd_parent) => push_cwd(sink, cwd_parent),
+                    None => {
+                        // don't care about `cwd` is "//" or "///"
+                        let rooted = components::is_root(cwd);
+
+                        if rooted {
+                            sink.push_root();
+                        }
+
+                        escape_policy.escape(to_path_buf, 0, sink, rooted)?;
+                    },
+                }
+            },
+            Component::Normal(token) => sink.push(token),
+        }
+
+        if push_components(to_path_buf, sink, iter, 1, escape_policy)? {
+            has_dots = true;
+        }
+
+        Ok(Some(has_dots))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The implementation of `join_confined`. The tokens are pushed into `sink` as if the path were relative to the root of `sink`, which must have been pushed. The root of the path escapes in the same way as the **Double Dots** climbing above the root of `sink`, so `escape_policy` must not be `EscapePolicy::Preserve`.
+#[cfg(not(windows))]
+pub(crate) fn dedot_confined<'a>(
+    path: &'a [u8],
+    escape_policy: EscapePolicy,
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+) -> io::Result<()> {
+    let mut iter = Components::new(path);
+
+    let mut start_index = 0;
+
+    if iter.clone().next() == Some(Component::RootDir) {
+        iter.next();
+
+        start_index = 1;
+
+        escape_policy.escape(to_path_buf, 0, sink, true)?;
+    }
+
+    push_components(to_path_buf, sink, iter, start_index, escape_policy)?;
+
+    Ok(())
+}
+
+/// The implementation of `is_dedotted`. Without dots, the tokens pushed by `dedot` are exactly the components of the path, so only the length of the joined path needs to be checked.
+pub(crate) fn is_dedotted_bytes(path: &[u8]) -> bool {
+    let mut size = 0;
+    let mut count = 0;
+
+    for component in Components::new(path) {
+        match component {
+            Component::RootDir => size += 1,
+            Component::CurDir | Component::ParentDir => return false,
+            Component::Normal(token) => {
+                size += token.len();
+                count += 1;
+            },
+        }
+    }
+
+    // the same as `JoinedPath`
+    size + count.max(1) - 1 == path.len()
+}
+
+/// The implementation of `needs_cwd`. `dedot` only uses `cwd` for the first component.
+#[inline]
+pub(crate) fn needs_cwd_bytes(path: &[u8]) -> bool {
+    matches!(Components::new(path).next(), Some(Component::CurDir | Component::ParentDir))
+}
+
+/// The implementation of `dedot_in_place_from`, which gives the same result as `parse_dot_bytes` with the default options. The buffer only grows when a leading **Single Dot** or **Double Dots** is replaced by `cwd`. Return whether the buffer is changed.
+pub(crate) fn dedot_in_place_bytes(buffer: &mut Vec<u8>, cwd: &[u8]) -> io::Result<bool> {
+    if is_dedotted_bytes(buffer) {
+        return Ok(false);
+    }
+
+    let head_end = match Components::new(buffer).next() {
+        Some(Component::RootDir) => {
+            return Ok(dedot_tail_in_place(buffer, 1, 1, SEPARATOR[0], |b| b == SEPARATOR[0]));
+        },
+        Some(Component::CurDir) => 1,
+        Some(Component::ParentDir) => 2,
+        _ => return Ok(dedot_tail_in_place(buffer, 0, 0, SEPARATOR[0], |b| b == SEPARATOR[0])),
+    };
+
+    // replace the leading Single Dot or Double Dots with the CWD or its parent
+    let head =
+        parse_dot_bytes(&buffer[..head_end], cwd, DedotOptions::new(), &PathBuf::new)?.into_owned();
+
+    let changed = buffer[..head_end] != head[..];
+
+    let start = head.len();
+    let base = usize::from(head.starts_with(SEPARATOR));
+
+    buffer.splice(..head_end, head);
+
+    Ok(dedot_tail_in_place(buffer, start, base, SEPARATOR[0], |b| b == SEPARATOR[0]) || changed)
+}
+
+/// The implementation of `clean`. The result is borrowed only if it is `path` itself.
+pub(crate) fn clean_bytes(path: &[u8]) -> Cow<'_, [u8]> {
+    let mut iter = Components::new(path);
+
+    let mut has_dots = false;
+
+    if let Some(first_component) = iter.next() {
+        let mut joined = JoinedPath::new(path, SEPARATOR[0]);
+
+        match first_component {
+            Component::RootDir => joined.push_root(),
+            Component::CurDir => has_dots = true,
+            _ => joined.push(first_component.as_bytes()),
+        }
+
+        // `EscapePolicy::Preserve` never fails
+        if push_components(&PathBuf::new, &mut joined, iter, 1, EscapePolicy::Preserve)
+            .unwrap_or(true)
+        {
+            has_dots = true;
+        }
+
+        if joined.is_empty() {
+            return if path == b"." { Cow::from(path) } else { Cow::from(b".".to_vec()) };
+        }
+
+        joined.into_cow(has_dots, false)
+    } else {
+        // like an empty result
+        Cow::from(b".".to_vec())
+    }
+}
+
+/// Push the remaining components of the path into `sink` and let **Double Dots** pop the last token. Return `true` if any component is removed or ignored.
+fn push_components<'a>(
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+    iter: Components<'a>,
+    start_index: usize,
+    escape_policy: EscapePolicy,
+) -> io::Result<bool> {
+    let mut has_dots = false;
+
+    for (index, component) in iter.enumerate().map(|(i, c)| (i + start_index, c)) {
+        match component {
+            Component::CurDir => {
+                // may be unreachable
+                has_dots = true;
+            },
+            Component::ParentDir => match sink.last() {
+                Some(last) if escape_policy != EscapePolicy::Preserve || last != b".." => {
+                    sink.pop();
+
+                    has_dots = true;
+                },
+                _ => {
+                    let rooted = sink.has_root();
+
+                    if escape_policy != EscapePolicy::Preserve || rooted {
+                        has_dots = true;
+                    }
+
+                    escape_policy.escape(to_path_buf, index, sink, rooted)?;
+                },
+            },
+            _ => sink.push(component.as_bytes()),
+        }
+    }
+
+    Ok(has_dots)
+}
+
+/// Whether a POSIX path ends with a separator, a **Single Dot** or **Double Dots**, which means it is a directory.
+pub(crate) fn ends_with_directory(path: &[u8]) -> bool {
+    path.ends_with(b"/")
+        || path == b"."
+        || path == b".."
+        || path.ends_with(b"/.")
+        || path.ends_with(b"/..")
+}
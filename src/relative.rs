@@ -0,0 +1,93 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+    path::{Component, Path, PathBuf, Prefix},
+};
+
+use crate::{DedotError, ParseDot, MAIN_SEPARATOR};
+
+/// Compute the path of `target` as seen from `base`. Both of them are dedotted with `cwd` first.
+pub(crate) fn relative_to(target: &Path, base: &Path, cwd: &Path) -> io::Result<PathBuf> {
+    let target = target.parse_dot_from(cwd)?;
+    let base = base.parse_dot_from(cwd)?;
+
+    let target_components: Vec<Component> = target.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+
+    let is_anchor = |c: &&Component| matches!(c, Component::Prefix(_) | Component::RootDir);
+
+    let target_anchor: Vec<&Component> = target_components.iter().take_while(is_anchor).collect();
+    let base_anchor: Vec<&Component> = base_components.iter().take_while(is_anchor).collect();
+
+    // the index of the first prefix or root which differs
+    let anchor_length = target_anchor
+        .iter()
+        .zip(base_anchor.iter())
+        .take_while(|(t, b)| same_component(t, b))
+        .count();
+
+    if anchor_length != target_anchor.len() || anchor_length != base_anchor.len() {
+        return Err(DedotError::PrefixMismatch {
+            path:  target.into_owned(),
+            base:  base.into_owned(),
+            index: anchor_length,
+        }
+        .into());
+    }
+
+    let common_length = target_components
+        .iter()
+        .zip(base_components.iter())
+        .take_while(|(t, b)| same_component(t, b))
+        .count();
+
+    let mut tokens: Vec<&OsStr> = Vec::new();
+
+    for (index, component) in base_components.iter().enumerate().skip(common_length) {
+        match component {
+            Component::Normal(_) => tokens.push(OsStr::new("..")),
+            _ => {
+                return Err(DedotError::Unresolvable {
+                    path: base.to_path_buf(),
+                    index,
+                }
+                .into());
+            },
+        }
+    }
+
+    for component in &target_components[common_length..] {
+        tokens.push(component.as_os_str());
+    }
+
+    if tokens.is_empty() {
+        return Ok(PathBuf::from("."));
+    }
+
+    let mut path_string = OsString::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            path_string.push(MAIN_SEPARATOR.as_os_str());
+        }
+
+        path_string.push(token);
+    }
+
+    Ok(PathBuf::from(path_string))
+}
+
+/// Compare two components like Windows does, where the server and share names of UNC prefixes are case-insensitive.
+#[inline]
+fn same_component(a: &Component, b: &Component) -> bool {
+    match (a, b) {
+        (Component::Prefix(a), Component::Prefix(b)) => match (a.kind(), b.kind()) {
+            (Prefix::UNC(a_server, a_share), Prefix::UNC(b_server, b_share))
+            | (Prefix::VerbatimUNC(a_server, a_share), Prefix::VerbatimUNC(b_server, b_share)) => {
+                a_server.eq_ignore_ascii_case(b_server) && a_share.eq_ignore_ascii_case(b_share)
+            },
+            (a, b) => a == b,
+        },
+        _ => a == b,
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{DedotError, MAIN_SEPARATOR};
+
+/// How to handle **Double Dots** which would remove a symbolic link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymlinkPolicy {
+    /// Stop collapsing and keep the **Double Dots**, e.g. `link/../x` stays `link/../x` if `link` is a symbolic link.
+    Keep,
+    /// Return a `DedotError::Symlink`, wrapped in an `io::Error` whose kind is `ErrorKind::InvalidInput`.
+    Error,
+}
+
+/// A component which was checked by the `parse_dot_symlink_aware` and `parse_dot_from_symlink_aware` methods before it was removed by **Double Dots**.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedComponent {
+    /// The path which was checked with `std::fs::symlink_metadata`.
+    pub path:       PathBuf,
+    /// The index of the **Double Dots** in the components of the original path.
+    pub index:      usize,
+    /// Whether `path` is a symbolic link. A path which does not exist is not a symbolic link.
+    pub is_symlink: bool,
+}
+
+/// The result of the `parse_dot_symlink_aware` and `parse_dot_from_symlink_aware` methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkAwarePath {
+    /// The dedotted path.
+    pub path:    PathBuf,
+    /// The components which were checked, in order.
+    pub checked: Vec<CheckedComponent>,
+}
+
+/// The error returned when **Double Dots** would remove a symbolic link under `SymlinkPolicy::Error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkError {
+    path:    PathBuf,
+    index:   usize,
+    symlink: PathBuf,
+}
+
+impl SymlinkError {
+    /// The path which is being dedotted.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// The index of the **Double Dots** in the components of the path.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The symbolic link which would be removed.
+    #[inline]
+    pub fn symlink(&self) -> &Path {
+        self.symlink.as_path()
+    }
+}
+
+impl Display for SymlinkError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the component at index {} of {:?} would remove the symbolic link {:?}",
+            self.index, self.path, self.symlink
+        )
+    }
+}
+
+impl Error for SymlinkError {}
+
+/// Remove dots in `path` like the `parse_dot_from` method does, but check each component which is about to be removed by **Double Dots** with `std::fs::symlink_metadata`.
+///
+/// The CWD is assumed to have no symbolic links, as the `std::env::current_dir` function returns.
+pub(crate) fn parse_dot_from_symlink_aware(
+    path: &Path,
+    cwd: &Path,
+    symlink_policy: SymlinkPolicy,
+) -> io::Result<SymlinkAwarePath> {
+    let mut anchor = PathBuf::new();
+    let mut tokens: Vec<&OsStr> = Vec::new();
+
+    let mut iter = path.components().enumerate().peekable();
+
+    // consume the prefix and the root of `path`, or start from `cwd`
+    let mut starts_from_cwd = false;
+
+    while let Some(&(_, component)) = iter.peek() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                anchor.push(component.as_os_str());
+
+                iter.next();
+            },
+            Component::CurDir | Component::ParentDir => {
+                starts_from_cwd = !anchor.has_root();
+
+                break;
+            },
+            Component::Normal(_) => break,
+        }
+    }
+
+    if starts_from_cwd {
+        let has_prefix = !anchor.as_os_str().is_empty();
+
+        for component in cwd.components() {
+            match component {
+                Component::Prefix(_) => {
+                    if !has_prefix {
+                        anchor.push(component.as_os_str());
+                    }
+                },
+                Component::RootDir => anchor.push(component.as_os_str()),
+                _ => tokens.push(component.as_os_str()),
+            }
+        }
+    }
+
+    // the tokens below it cannot be popped
+    let mut barrier = 0;
+
+    let mut checked = Vec::new();
+
+    for (index, component) in iter {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                let tokens_length = tokens.len();
+
+                if tokens_length > barrier {
+                    let candidate = join(&anchor, &tokens);
+
+                    let candidate_to_check =
+                        if candidate.has_root() { candidate.clone() } else { cwd.join(&candidate) };
+
+                    let is_symlink = match fs::symlink_metadata(candidate_to_check) {
+                        Ok(metadata) => metadata.file_type().is_symlink(),
+                        Err(error) if error.kind() == ErrorKind::NotFound => false,
+                        Err(error) => return Err(error),
+                    };
+
+                    checked.push(CheckedComponent {
+                        path: candidate.clone(),
+                        index,
+                        is_symlink,
+                    });
+
+                    if is_symlink {
+                        match symlink_policy {
+                            SymlinkPolicy::Keep => {
+                                tokens.push(component.as_os_str());
+
+                                barrier = tokens.len();
+                            },
+                            SymlinkPolicy::Error => {
+                                return Err(DedotError::Symlink(SymlinkError {
+                                    path: path.to_path_buf(),
+                                    index,
+                                    symlink: candidate,
+                                })
+                                .into());
+                            },
+                        }
+                    } else {
+                        tokens.pop();
+                    }
+                } else if barrier > 0 {
+                    // keep climbing after the kept Double Dots
+                    tokens.push(component.as_os_str());
+
+                    barrier = tokens.len();
+                }
+            },
+            _ => tokens.push(component.as_os_str()),
+        }
+    }
+
+    Ok(SymlinkAwarePath {
+        path: join(&anchor, &tokens),
+        checked,
+    })
+}
+
+/// Join `tokens` to `anchor`, which only contains a prefix and a root, without parsing the tokens again.
+fn join(anchor: &Path, tokens: &[&OsStr]) -> PathBuf {
+    let mut path_string = OsString::from(anchor.as_os_str());
+
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            path_string.push(MAIN_SEPARATOR.as_os_str());
+        }
+
+        path_string.push(token);
+    }
+
+    PathBuf::from(path_string)
+}
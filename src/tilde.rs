@@ -0,0 +1,172 @@
+use std::{
+    borrow::Cow,
+    env,
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt::{self, Display, Formatter},
+    io::{self, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::DedotError;
+
+/// The error returned when the home directory in a path cannot be found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TildeError {
+    /// The home directory of the current user cannot be found in `$HOME` or in the passwd database.
+    NoHomeDir,
+    /// The user in `~user` does not exist.
+    NoSuchUser(OsString),
+    /// `~user` is not supported on this platform.
+    UserUnsupported(OsString),
+}
+
+impl Display for TildeError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TildeError::NoHomeDir => f.write_str("the home directory cannot be found"),
+            TildeError::NoSuchUser(user) => write!(f, "the user {:?} does not exist", user),
+            TildeError::UserUnsupported(user) => {
+                write!(f, "the home directory of the user {:?} is not supported", user)
+            },
+        }
+    }
+}
+
+impl Error for TildeError {}
+
+impl From<TildeError> for io::Error {
+    #[inline]
+    fn from(error: TildeError) -> Self {
+        io::Error::new(ErrorKind::NotFound, error)
+    }
+}
+
+/// Expand the tilde for the `expand_tilde` option, with the error wrapped in a `DedotError`.
+#[inline]
+pub(crate) fn expand_tilde_option(path: &Path) -> io::Result<Cow<'_, Path>> {
+    expand_tilde(path).map_err(|source| {
+        DedotError::Tilde {
+            path: path.to_path_buf(),
+            source,
+        }
+        .into()
+    })
+}
+
+/// Replace a leading `~` in the path with the home directory of the current user, and a leading `~user` with the home directory of `user`. Only the first component is expanded, and the path is returned as it is if the first component does not start with `~`.
+///
+/// The home directory of the current user comes from the `HOME` environment variable (or `USERPROFILE` on Windows), with a fallback to the passwd database on Unix. The home directories of other users come from the passwd database, so `~user` is only supported on Unix.
+pub fn expand_tilde(path: &Path) -> Result<Cow<'_, Path>, TildeError> {
+    let mut iter = path.components();
+
+    let user = match iter.next() {
+        Some(Component::Normal(token)) => match token.as_encoded_bytes() {
+            [b'~', user @ ..] => user,
+            _ => return Ok(Cow::from(path)),
+        },
+        _ => return Ok(Cow::from(path)),
+    };
+
+    let home_dir = if user.is_empty() {
+        current_home_dir().ok_or(TildeError::NoHomeDir)?
+    } else {
+        // SAFETY: the token is only cut next to the ASCII `~`, and `user` does not need to be UTF-8
+        PathBuf::from(user_home_dir(unsafe { OsStr::from_encoded_bytes_unchecked(user) })?)
+    };
+
+    let rest = iter.as_path();
+
+    if rest.as_os_str().is_empty() {
+        Ok(Cow::from(home_dir))
+    } else {
+        Ok(Cow::from(home_dir.join(rest)))
+    }
+}
+
+fn current_home_dir() -> Option<PathBuf> {
+    let home_dir = env::var_os("HOME").filter(|h| !h.is_empty());
+
+    #[cfg(windows)]
+    let home_dir = home_dir.or_else(|| env::var_os("USERPROFILE").filter(|h| !h.is_empty()));
+
+    #[cfg(unix)]
+    let home_dir = home_dir.or_else(|| passwd_home_dir(None));
+
+    home_dir.map(PathBuf::from)
+}
+
+#[cfg(unix)]
+#[inline]
+fn user_home_dir(user: &OsStr) -> Result<OsString, TildeError> {
+    passwd_home_dir(Some(user)).ok_or_else(|| TildeError::NoSuchUser(user.to_os_string()))
+}
+
+#[cfg(not(unix))]
+#[inline]
+fn user_home_dir(user: &OsStr) -> Result<OsString, TildeError> {
+    Err(TildeError::UserUnsupported(user.to_os_string()))
+}
+
+/// Look up the home directory of `user`, or of the current user if `user` is `None`, in the passwd database.
+#[cfg(unix)]
+fn passwd_home_dir(user: Option<&OsStr>) -> Option<OsString> {
+    use std::{
+        ffi::{CStr, CString},
+        mem,
+        os::unix::ffi::{OsStrExt, OsStringExt},
+        ptr,
+    };
+
+    let user = match user {
+        Some(user) => Some(CString::new(user.as_bytes()).ok()?),
+        None => None,
+    };
+
+    let mut buffer: Vec<std::os::raw::c_char> = vec![0; 1024];
+
+    loop {
+        let mut passwd: libc::passwd = unsafe { mem::zeroed() };
+        let mut result: *mut libc::passwd = ptr::null_mut();
+
+        let code = unsafe {
+            match user.as_ref() {
+                Some(user) => libc::getpwnam_r(
+                    user.as_ptr(),
+                    &mut passwd,
+                    buffer.as_mut_ptr(),
+                    buffer.len(),
+                    &mut result,
+                ),
+                None => libc::getpwuid_r(
+                    libc::getuid(),
+                    &mut passwd,
+                    buffer.as_mut_ptr(),
+                    buffer.len(),
+                    &mut result,
+                ),
+            }
+        };
+
+        if code == libc::ERANGE && buffer.len() < 1024 * 1024 {
+            let length = buffer.len() * 2;
+
+            buffer.resize(length, 0);
+
+            continue;
+        }
+
+        if code != 0 || result.is_null() || passwd.pw_dir.is_null() {
+            return None;
+        }
+
+        let home_dir = unsafe { CStr::from_ptr(passwd.pw_dir) }.to_bytes();
+
+        if home_dir.is_empty() {
+            return None;
+        }
+
+        return Some(OsString::from_vec(home_dir.to_vec()));
+    }
+}
@@ -0,0 +1,383 @@
+use std::borrow::Cow;
+
+use crate::escape::ParentDirTokens;
+
+/// Where the tokens of a dedotted path go. The prefix and the root are pushed first, and the rest are normal tokens, which can be popped.
+pub(crate) trait TokenSink<'a>: ParentDirTokens {
+    /// Push the prefix of a Windows path, which must be the first token.
+    fn push_prefix(&mut self, prefix: &'a [u8]);
+
+    /// Push the root, which must follow the prefix, if any.
+    fn push_root(&mut self);
+
+    /// Push a normal token, which can also be **Double Dots** kept by `EscapePolicy::Preserve`.
+    fn push(&mut self, token: &'a [u8]);
+
+    /// Remove the last normal token. There must be one.
+    fn pop(&mut self);
+
+    /// The last normal token.
+    fn last(&self) -> Option<&[u8]>;
+
+    fn has_prefix(&self) -> bool;
+
+    fn has_root(&self) -> bool;
+
+    /// Whether nothing, not even the prefix or the root, has been pushed.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.last().is_none() && !self.has_prefix() && !self.has_root()
+    }
+}
+
+/// Collect the tokens, for `DedotComponents`.
+#[derive(Debug, Default)]
+pub(crate) struct TokenVec<'a> {
+    pub(crate) tokens:     Vec<&'a [u8]>,
+    pub(crate) has_prefix: bool,
+    pub(crate) has_root:   bool,
+}
+
+impl<'a> TokenSink<'a> for TokenVec<'a> {
+    #[inline]
+    fn push_prefix(&mut self, prefix: &'a [u8]) {
+        self.tokens.push(prefix);
+
+        self.has_prefix = true;
+    }
+
+    #[inline]
+    fn push_root(&mut self) {
+        // the bytes of the root are never used
+        self.tokens.push(b"");
+
+        self.has_root = true;
+    }
+
+    #[inline]
+    fn push(&mut self, token: &'a [u8]) {
+        self.tokens.push(token);
+    }
+
+    #[inline]
+    fn pop(&mut self) {
+        self.tokens.pop();
+    }
+
+    #[inline]
+    fn last(&self) -> Option<&[u8]> {
+        if self.tokens.len() > usize::from(self.has_prefix) + usize::from(self.has_root) {
+            self.tokens.last().copied()
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn has_prefix(&self) -> bool {
+        self.has_prefix
+    }
+
+    #[inline]
+    fn has_root(&self) -> bool {
+        self.has_root
+    }
+}
+
+impl ParentDirTokens for TokenVec<'_> {
+    #[inline]
+    fn push_parent_dir(&mut self) {
+        self.tokens.push(b"..");
+    }
+}
+
+/// Join the tokens with the separator in a single pass.
+///
+/// As long as the pushed tokens follow each other in the original path, they are joined by extending `path[..length]`, and popping them is a truncation. The tokens which come from somewhere else are kept in a stack after it. The result is written into one buffer of the exact size at the end.
+#[derive(Debug)]
+pub(crate) struct JoinedPath<'p, 'a> {
+    path:          &'p [u8],
+    separator:     u8,
+    /// The prefix if it is not at the beginning of `path`.
+    head:          &'a [u8],
+    prefix_length: usize,
+    has_prefix:    bool,
+    has_root:      bool,
+    /// Whether the root is not in `path[..length]` but after it, in which case `path[..length]` can no longer be extended.
+    root_after:    bool,
+    length:        usize,
+    /// The length of the prefix and the root in `path[..length]`.
+    base:          usize,
+    /// The number of normal tokens in `path[..length]`.
+    count:         usize,
+    /// The start of the last normal token in `path[..length]`.
+    last_start:    usize,
+    /// The normal tokens after `path[..length]`.
+    tokens:        Vec<&'a [u8]>,
+}
+
+impl<'p: 'a, 'a> JoinedPath<'p, 'a> {
+    #[inline]
+    pub(crate) fn new(path: &'p [u8], separator: u8) -> JoinedPath<'p, 'a> {
+        JoinedPath {
+            path,
+            separator,
+            head: b"",
+            prefix_length: 0,
+            has_prefix: false,
+            has_root: false,
+            root_after: false,
+            length: 0,
+            base: 0,
+            count: 0,
+            last_start: 0,
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Whether `bytes` can be joined by extending `path[..length]`.
+    #[inline]
+    fn follows(&self, with_separator: bool, bytes: &[u8]) -> bool {
+        let start = self.length + usize::from(with_separator);
+        let end = start + bytes.len();
+
+        end <= self.path.len()
+            && (!with_separator || self.path[self.length] == self.separator)
+            && (self.path[start..].as_ptr() == bytes.as_ptr() || &self.path[start..end] == bytes)
+    }
+
+    /// Finish joining. The original path is returned if nothing is removed or ignored (`has_dots` is `false`) and the length does not change. A separator is appended if `trailing_separator` is `true` and the path does not end with the prefix or the root.
+    pub(crate) fn into_cow(self, has_dots: bool, trailing_separator: bool) -> Cow<'p, [u8]> {
+        let count = self.count + self.tokens.len();
+
+        if !has_dots
+            && self.has_prefix
+            && self.has_root
+            && count == 0
+            && self.prefix_length == self.path.len()
+        {
+            // `\\server\share` -> `\\server\share\` should still be `\\server\share`
+            return Cow::from(self.path);
+        }
+
+        let trailing_separator = trailing_separator && count > 0;
+
+        let separators =
+            if self.count > 0 { self.tokens.len() } else { self.tokens.len().saturating_sub(1) };
+
+        let size = self.head.len()
+            + self.length
+            + usize::from(self.root_after)
+            + self.tokens.iter().fold(separators, |acc, token| acc + token.len())
+            + usize::from(trailing_separator);
+
+        if !has_dots && size == self.path.len() {
+            return Cow::from(self.path);
+        }
+
+        let mut path = Vec::with_capacity(size);
+
+        path.extend_from_slice(self.head);
+        path.extend_from_slice(&self.path[..self.length]);
+
+        if self.root_after {
+            path.push(self.separator);
+        }
+
+        let mut iter = self.tokens.iter();
+
+        if self.count == 0 {
+            if let Some(token) = iter.next() {
+                path.extend_from_slice(token);
+            }
+        }
+
+        for token in iter {
+            path.push(self.separator);
+            path.extend_from_slice(token);
+        }
+
+        if trailing_separator {
+            path.push(self.separator);
+        }
+
+        Cow::from(path)
+    }
+}
+
+impl<'p: 'a, 'a> TokenSink<'a> for JoinedPath<'p, 'a> {
+    #[inline]
+    fn push_prefix(&mut self, prefix: &'a [u8]) {
+        if self.follows(false, prefix) {
+            self.length = prefix.len();
+            self.base = self.length;
+            self.last_start = self.base;
+        } else {
+            self.head = prefix;
+        }
+
+        self.prefix_length = prefix.len();
+        self.has_prefix = true;
+    }
+
+    #[inline]
+    fn push_root(&mut self) {
+        if self.follows(false, &[self.separator]) {
+            self.length += 1;
+            self.base = self.length;
+            self.last_start = self.base;
+        } else {
+            self.root_after = true;
+        }
+
+        self.has_root = true;
+    }
+
+    #[inline]
+    fn push(&mut self, token: &'a [u8]) {
+        if self.tokens.is_empty() && !self.root_after {
+            let with_separator = self.count > 0;
+
+            if self.follows(with_separator, token) {
+                self.last_start = self.length + usize::from(with_separator);
+                self.length = self.last_start + token.len();
+                self.count += 1;
+
+                return;
+            }
+        }
+
+        self.tokens.push(token);
+    }
+
+    #[inline]
+    fn pop(&mut self) {
+        if self.tokens.pop().is_none() {
+            // remove the separator before the token as well
+            self.length = self.last_start.saturating_sub(1).max(self.base);
+            self.count -= 1;
+
+            // normal tokens never contain the separator
+            let separator = self.separator;
+
+            self.last_start =
+                match self.path[self.base..self.length].iter().rposition(|&b| b == separator) {
+                    Some(index) => self.base + index + 1,
+                    None => self.base,
+                };
+        }
+    }
+
+    #[inline]
+    fn last(&self) -> Option<&[u8]> {
+        match self.tokens.last() {
+            Some(token) => Some(token),
+            None if self.count > 0 => Some(&self.path[self.last_start..self.length]),
+            None => None,
+        }
+    }
+
+    #[inline]
+    fn has_prefix(&self) -> bool {
+        self.has_prefix
+    }
+
+    #[inline]
+    fn has_root(&self) -> bool {
+        self.has_root
+    }
+}
+
+impl<'p: 'a, 'a> ParentDirTokens for JoinedPath<'p, 'a> {
+    #[inline]
+    fn push_parent_dir(&mut self) {
+        self.push(b"..");
+    }
+}
+
+/// Remove dots in `buffer[start..]` in place. `buffer[..start]` has been dedotted, and its prefix and root take `base` bytes. The tokens are moved towards the start and joined with `separator`, popping a token is a truncation, and **Double Dots** which cannot pop a token are ignored, like `EscapePolicy::Clamp`. Return whether the buffer is changed.
+pub(crate) fn dedot_tail_in_place(
+    buffer: &mut Vec<u8>,
+    start: usize,
+    base: usize,
+    separator: u8,
+    is_separator: impl Fn(u8) -> bool,
+) -> bool {
+    let path_length = buffer.len();
+
+    let mut changed = false;
+
+    // the normal tokens of `buffer[..start]`, which are joined with `separator`
+    let (mut count, mut last_start) =
+        match buffer[base..start].iter().rposition(|&b| b == separator) {
+            Some(index) => (
+                buffer[base..start].iter().filter(|&&b| b == separator).count() + 1,
+                base + index + 1,
+            ),
+            None => (usize::from(start > base), base),
+        };
+
+    let mut length = start;
+    let mut index = start;
+
+    while index < path_length {
+        if is_separator(buffer[index]) {
+            index += 1;
+
+            continue;
+        }
+
+        let end = match buffer[index..].iter().position(|&b| is_separator(b)) {
+            Some(i) => index + i,
+            None => path_length,
+        };
+
+        match &buffer[index..end] {
+            b"." => (),
+            b".." => {
+                if count > 0 {
+                    // remove the separator before the token as well
+                    length = last_start.saturating_sub(1).max(base);
+                    count -= 1;
+
+                    last_start = match buffer[base..length].iter().rposition(|&b| b == separator) {
+                        Some(i) if count > 0 => base + i + 1,
+                        _ => base,
+                    };
+                }
+            },
+            _ => {
+                // the token is always after the written bytes and at least one separator, if it is not the first token
+                if count > 0 {
+                    if buffer[length] != separator {
+                        buffer[length] = separator;
+
+                        changed = true;
+                    }
+
+                    length += 1;
+                }
+
+                if index != length {
+                    buffer.copy_within(index..end, length);
+
+                    changed = true;
+                }
+
+                last_start = length;
+                length += end - index;
+                count += 1;
+            },
+        }
+
+        index = end;
+    }
+
+    if length != path_length {
+        buffer.truncate(length);
+
+        changed = true;
+    }
+
+    changed
+}
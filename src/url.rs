@@ -0,0 +1,415 @@
+/*!
+Dedot the paths of URLs, and convert between file paths and `file:` URLs.
+
+The path of a URL is not a file path. Its dot segments are removed by the algorithm in RFC 3986 §5.2.4, which keeps the trailing `/` after a dot segment and only treats `/` as a separator.
+*/
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    ffi::OsStr,
+    fmt::{self, Display, Formatter},
+    io::{self, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::Clean;
+
+/// The error returned when a file path and a `file:` URL cannot be converted to each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileUrlError {
+    /// The URL does not start with `file:`.
+    NotFileScheme,
+    /// The URL has a host which cannot be represented on this platform. Only Windows maps hosts to UNC paths.
+    UnsupportedHost(String),
+    /// The `%` at the byte offset is not followed by two hexadecimal digits.
+    InvalidPercentEncoding(usize),
+    /// The `%` at the byte offset encodes a separator, which cannot be a part of a file name.
+    EncodedSeparator(usize),
+    /// The path is not valid UTF-8, which is required on this platform.
+    NotUtf8,
+    /// The path is not absolute.
+    NotAbsolute,
+    /// The path has a prefix which has no URL form, e.g. `\\?\pipe` or `\\.\COM1`.
+    UnsupportedPrefix,
+}
+
+impl Display for FileUrlError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FileUrlError::NotFileScheme => f.write_str("the URL is not a file URL"),
+            FileUrlError::UnsupportedHost(host) => {
+                write!(f, "the host {:?} is not supported on this platform", host)
+            },
+            FileUrlError::InvalidPercentEncoding(offset) => {
+                write!(f, "the percent-encoding at offset {} is invalid", offset)
+            },
+            FileUrlError::EncodedSeparator(offset) => {
+                write!(f, "the percent-encoding at offset {} is a separator", offset)
+            },
+            FileUrlError::NotUtf8 => f.write_str("the path is not valid UTF-8"),
+            FileUrlError::NotAbsolute => f.write_str("the path is not absolute"),
+            FileUrlError::UnsupportedPrefix => {
+                f.write_str("the prefix of the path has no URL form")
+            },
+        }
+    }
+}
+
+impl Error for FileUrlError {}
+
+impl From<FileUrlError> for io::Error {
+    #[inline]
+    fn from(error: FileUrlError) -> Self {
+        io::Error::new(ErrorKind::InvalidInput, error)
+    }
+}
+
+/// Remove the dot segments in the path of a URL with the algorithm in RFC 3986 §5.2.4.
+///
+/// * A dot segment is `.` or `..`, in which any dot can be percent-encoded as `%2e` or `%2E`.
+/// * A segment with parameters, e.g. `..;x`, is not a dot segment.
+/// * The query and the fragment, which start from the first `?` or `#`, are kept as they are.
+/// * Like `EscapePolicy::Clamp`, the **Double Dots** which climb above the root are ignored.
+///
+/// ```
+/// use path_dedot::url;
+///
+/// assert_eq!("/a/g", url::remove_dot_segments("/a/b/c/./../../g"));
+/// assert_eq!("mid/6", url::remove_dot_segments("mid/content=5/../6"));
+/// assert_eq!("/a/", url::remove_dot_segments("/a/b/%2E%2e"));
+/// assert_eq!("/a/?b=../c", url::remove_dot_segments("/a/b/..?b=../c"));
+/// ```
+pub fn remove_dot_segments(url_path: &str) -> Cow<'_, str> {
+    let (path, suffix) = match url_path.find(['?', '#']) {
+        Some(index) => url_path.split_at(index),
+        None => (url_path, ""),
+    };
+
+    if !path.split('/').any(|segment| is_dot(segment) || is_double_dots(segment)) {
+        return Cow::from(url_path);
+    }
+
+    let mut input = path;
+    let mut output = String::with_capacity(path.len() + suffix.len());
+
+    while !input.is_empty() {
+        let (segment, rest) = match input.strip_prefix('/') {
+            Some(input) => split_segment(input),
+            None => split_segment(input),
+        };
+
+        if !input.starts_with('/') {
+            if is_dot(segment) || is_double_dots(segment) {
+                // A. remove a leading `./` or `../`
+                // D. remove a single `.` or `..`
+                input = rest.strip_prefix('/').unwrap_or(rest);
+
+                continue;
+            }
+        } else if is_dot(segment) {
+            // B. replace a leading `/./` or `/.` with `/`
+            input = if rest.is_empty() { "/" } else { rest };
+
+            continue;
+        } else if is_double_dots(segment) {
+            // C. replace a leading `/../` or `/..` with `/`, and remove the last segment of the output
+            output.truncate(output.rfind('/').unwrap_or(0));
+
+            input = if rest.is_empty() { "/" } else { rest };
+
+            continue;
+        }
+
+        // E. move the first segment, with its leading `/`, to the output
+        let length = input.len() - rest.len();
+
+        output.push_str(&input[..length]);
+
+        input = rest;
+    }
+
+    output.push_str(suffix);
+
+    Cow::from(output)
+}
+
+/// Split the first segment from `input`. The rest starts with `/` if it is not empty.
+#[inline]
+fn split_segment(input: &str) -> (&str, &str) {
+    match input.find('/') {
+        Some(index) => input.split_at(index),
+        None => (input, ""),
+    }
+}
+
+/// Whether the segment is `.`, `%2e` or `%2E`.
+#[inline]
+fn is_dot(segment: &str) -> bool {
+    is_dot_bytes(segment.as_bytes())
+}
+
+/// Whether the segment is made of two dots, each of which is `.`, `%2e` or `%2E`.
+#[inline]
+fn is_double_dots(segment: &str) -> bool {
+    let segment = segment.as_bytes();
+
+    match segment.len() {
+        2 => segment == b"..",
+        4 => {
+            (is_dot_bytes(&segment[..1]) && is_dot_bytes(&segment[1..]))
+                || (is_dot_bytes(&segment[..3]) && is_dot_bytes(&segment[3..]))
+        },
+        6 => is_dot_bytes(&segment[..3]) && is_dot_bytes(&segment[3..]),
+        _ => false,
+    }
+}
+
+#[inline]
+fn is_dot_bytes(segment: &[u8]) -> bool {
+    segment == b"." || segment.eq_ignore_ascii_case(b"%2e")
+}
+
+/// Convert a `file:` URL to a native path without dots.
+///
+/// * The path is percent-decoded, and then cleaned like the `clean` method. An encoded separator, e.g. `%2F`, is an error instead of a separator.
+/// * The query and the fragment are ignored. An empty host and `localhost` mean the local machine.
+/// * On Windows, `file:///C:/x` (or the legacy `file:///C|/x`) becomes `C:\x`, and `file://server/share/x` (or `file:////server/share/x`) becomes `\\server\share\x`. Other platforms do not support hosts.
+///
+/// ```
+/// use std::path::Path;
+///
+/// use path_dedot::url;
+///
+/// # #[cfg(not(windows))]
+/// assert_eq!(
+///     Path::new("/srv/my docs"),
+///     url::from_file_url("file:///srv/app/../my%20docs").unwrap()
+/// );
+/// # #[cfg(windows)]
+/// assert_eq!(
+///     Path::new(r"C:\my docs"),
+///     url::from_file_url("file:///C:/app/../my%20docs").unwrap()
+/// );
+/// ```
+pub fn from_file_url(url: &str) -> Result<PathBuf, FileUrlError> {
+    let rest = match url.get(..5) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("file:") => &url[5..],
+        _ => return Err(FileUrlError::NotFileScheme),
+    };
+
+    let rest = match rest.find(['?', '#']) {
+        Some(index) => &rest[..index],
+        None => rest,
+    };
+
+    let (host, path, path_offset) = match rest.strip_prefix("//") {
+        Some(authority) => {
+            let index = authority.find('/').unwrap_or(authority.len());
+
+            (&authority[..index], &authority[index..], 7 + index)
+        },
+        None => ("", rest, 5),
+    };
+
+    let host = if host.eq_ignore_ascii_case("localhost") { "" } else { host };
+
+    #[cfg(windows)]
+    let path = windows_path(percent_decode(host, 7)?, percent_decode(path, path_offset)?)?;
+
+    #[cfg(not(windows))]
+    let path = {
+        if !host.is_empty() {
+            return Err(FileUrlError::UnsupportedHost(host.to_string()));
+        }
+
+        posix_path(percent_decode(path, path_offset)?)?
+    };
+
+    if !path.is_absolute() {
+        return Err(FileUrlError::NotAbsolute);
+    }
+
+    Ok(path.clean().into_owned())
+}
+
+/// Convert an absolute native path to a `file:` URL. The path is cleaned like the `clean` method, and its names are percent-encoded.
+///
+/// On Windows, the `Disk` and `VerbatimDisk` prefixes become `file:///C:/`, and the `UNC` and `VerbatimUNC` prefixes become `file://server/share/`. Other prefixes are not supported.
+///
+/// ```
+/// use std::path::Path;
+///
+/// use path_dedot::url;
+///
+/// # #[cfg(not(windows))]
+/// assert_eq!(
+///     "file:///srv/my%20docs",
+///     url::to_file_url(Path::new("/srv/app/../my docs")).unwrap()
+/// );
+/// # #[cfg(windows)]
+/// assert_eq!(
+///     "file:///C:/my%20docs",
+///     url::to_file_url(Path::new(r"C:\app\..\my docs")).unwrap()
+/// );
+/// ```
+pub fn to_file_url(path: &Path) -> Result<String, FileUrlError> {
+    if !path.is_absolute() {
+        return Err(FileUrlError::NotAbsolute);
+    }
+
+    let path = path.clean();
+
+    let mut url = String::from("file://");
+    let mut has_name = false;
+
+    for component in path.components() {
+        match component {
+            #[cfg(windows)]
+            Component::Prefix(prefix) => {
+                use std::path::Prefix;
+
+                match prefix.kind() {
+                    Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => {
+                        url.push('/');
+                        url.push(drive as char);
+                        url.push(':');
+                    },
+                    Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                        percent_encode(&mut url, os_str_bytes(server)?);
+                        url.push('/');
+                        percent_encode(&mut url, os_str_bytes(share)?);
+                    },
+                    _ => return Err(FileUrlError::UnsupportedPrefix),
+                }
+            },
+            Component::Normal(name) => {
+                url.push('/');
+                percent_encode(&mut url, os_str_bytes(name)?);
+
+                has_name = true;
+            },
+            _ => (),
+        }
+    }
+
+    if !has_name {
+        url.push('/');
+    }
+
+    Ok(url)
+}
+
+/// Build a Windows path from the decoded host and path of a `file:` URL.
+#[cfg(windows)]
+fn windows_path(host: Vec<u8>, path: Vec<u8>) -> Result<PathBuf, FileUrlError> {
+    let host = String::from_utf8(host).map_err(|_| FileUrlError::NotUtf8)?;
+    let path = String::from_utf8(path).map_err(|_| FileUrlError::NotUtf8)?;
+
+    let mut windows_path = String::with_capacity(host.len() + path.len() + 2);
+
+    if !host.is_empty() {
+        // `file://server/share/x` is `\\server\share\x`
+        windows_path.push_str("//");
+        windows_path.push_str(&host);
+        windows_path.push_str(&path);
+    } else {
+        match path.as_bytes() {
+            // `file:///C:/x` and `file:///C|/x` are `C:\x`
+            [b'/', drive, b':' | b'|', rest @ ..]
+                if drive.is_ascii_alphabetic() && matches!(rest.first(), None | Some(b'/')) =>
+            {
+                windows_path.push(*drive as char);
+                windows_path.push(':');
+                windows_path.push_str(if rest.is_empty() { "/" } else { &path[3..] });
+            },
+            // `file:////server/share/x` is also `\\server\share\x`
+            _ => windows_path.push_str(&path),
+        }
+    }
+
+    Ok(PathBuf::from(windows_path.replace('/', "\\")))
+}
+
+/// Build a POSIX path from the decoded path of a `file:` URL.
+#[cfg(not(windows))]
+#[inline]
+fn posix_path(path: Vec<u8>) -> Result<PathBuf, FileUrlError> {
+    #[cfg(unix)]
+    {
+        use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+        Ok(PathBuf::from(OsString::from_vec(path)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(path).map(PathBuf::from).map_err(|_| FileUrlError::NotUtf8)
+    }
+}
+
+/// Get the bytes of a name in a path for percent-encoding. Names must be valid UTF-8 on platforms other than Unix.
+#[inline]
+fn os_str_bytes(name: &OsStr) -> Result<&[u8], FileUrlError> {
+    #[cfg(unix)]
+    {
+        Ok(name.as_encoded_bytes())
+    }
+
+    #[cfg(not(unix))]
+    {
+        name.to_str().map(str::as_bytes).ok_or(FileUrlError::NotUtf8)
+    }
+}
+
+/// Decode the percent-encoded bytes in a part of a URL which starts at `offset` in the URL.
+fn percent_decode(part: &str, offset: usize) -> Result<Vec<u8>, FileUrlError> {
+    let part = part.as_bytes();
+
+    let mut bytes = Vec::with_capacity(part.len());
+    let mut index = 0;
+
+    while index < part.len() {
+        if part[index] != b'%' {
+            bytes.push(part[index]);
+            index += 1;
+
+            continue;
+        }
+
+        let b = match (part.get(index + 1).and_then(hex), part.get(index + 2).and_then(hex)) {
+            (Some(high), Some(low)) => high << 4 | low,
+            _ => return Err(FileUrlError::InvalidPercentEncoding(offset + index)),
+        };
+
+        if b == b'/' || (cfg!(windows) && b == b'\\') {
+            return Err(FileUrlError::EncodedSeparator(offset + index));
+        }
+
+        bytes.push(b);
+        index += 3;
+    }
+
+    Ok(bytes)
+}
+
+#[inline]
+fn hex(b: &u8) -> Option<u8> {
+    (*b as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Append the bytes to a URL, and percent-encode the ones which are not allowed in a path segment.
+fn percent_encode(url: &mut String, bytes: &[u8]) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    for &b in bytes {
+        if b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&b) {
+            url.push(b as char);
+        } else {
+            url.push('%');
+            url.push(HEX[(b >> 4) as usize] as char);
+            url.push(HEX[(b & 0xF) as usize] as char);
+        }
+    }
+}
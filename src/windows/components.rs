@@ -0,0 +1,346 @@
+use std::path::Prefix;
+
+use crate::path_str::bytes_to_os_str;
+
+/// The prefix of a Windows path, which is the same as `std::path::Prefix` but holds bytes.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RawPrefix<'a> {
+    Verbatim(&'a [u8]),
+    VerbatimUNC(&'a [u8], &'a [u8]),
+    VerbatimDisk(u8),
+    DeviceNS(&'a [u8]),
+    UNC(&'a [u8], &'a [u8]),
+    Disk(u8),
+}
+
+impl<'a> RawPrefix<'a> {
+    /// The length of the prefix in bytes.
+    #[inline]
+    pub(crate) fn len(self) -> usize {
+        let optional_len = |s: &[u8]| if s.is_empty() { 0 } else { 1 + s.len() };
+
+        match self {
+            RawPrefix::Verbatim(prefix) => 4 + prefix.len(),
+            RawPrefix::VerbatimUNC(server, share) => 8 + server.len() + optional_len(share),
+            RawPrefix::VerbatimDisk(_) => 6,
+            RawPrefix::DeviceNS(device) => 4 + device.len(),
+            RawPrefix::UNC(server, share) => 2 + server.len() + optional_len(share),
+            RawPrefix::Disk(_) => 2,
+        }
+    }
+
+    #[inline]
+    fn is_verbatim(self) -> bool {
+        matches!(
+            self,
+            RawPrefix::Verbatim(_) | RawPrefix::VerbatimUNC(..) | RawPrefix::VerbatimDisk(_)
+        )
+    }
+
+    #[inline]
+    fn has_implicit_root(self) -> bool {
+        !matches!(self, RawPrefix::Disk(_))
+    }
+
+    /// Convert to `std::path::Prefix`.
+    ///
+    /// # Safety
+    ///
+    /// The prefix must be parsed from the bytes of an `OsStr`.
+    #[inline]
+    pub(crate) unsafe fn to_prefix(self) -> Prefix<'a> {
+        match self {
+            RawPrefix::Verbatim(prefix) => Prefix::Verbatim(bytes_to_os_str(prefix)),
+            RawPrefix::VerbatimUNC(server, share) => {
+                Prefix::VerbatimUNC(bytes_to_os_str(server), bytes_to_os_str(share))
+            },
+            RawPrefix::VerbatimDisk(drive) => Prefix::VerbatimDisk(drive),
+            RawPrefix::DeviceNS(device) => Prefix::DeviceNS(bytes_to_os_str(device)),
+            RawPrefix::UNC(server, share) => {
+                Prefix::UNC(bytes_to_os_str(server), bytes_to_os_str(share))
+            },
+            RawPrefix::Disk(drive) => Prefix::Disk(drive),
+        }
+    }
+}
+
+/// A component of a Windows path, which is parsed in the same way as `std::path::Component` on Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Component<'a> {
+    Prefix(&'a [u8]),
+    RootDir,
+    CurDir,
+    ParentDir,
+    Normal(&'a [u8]),
+}
+
+impl<'a> Component<'a> {
+    #[inline]
+    pub(crate) fn as_bytes(self) -> &'a [u8] {
+        match self {
+            Component::Prefix(prefix) => prefix,
+            Component::RootDir => br"\",
+            Component::CurDir => b".",
+            Component::ParentDir => b"..",
+            Component::Normal(token) => token,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Prefix,
+    StartDir,
+    Body,
+    Done,
+}
+
+/// An iterator over the components of a Windows path, which behaves the same as `std::path::Components` on Windows.
+#[derive(Debug, Clone)]
+pub(crate) struct Components<'a> {
+    path:              &'a [u8],
+    prefix:            Option<RawPrefix<'a>>,
+    has_physical_root: bool,
+    state:             State,
+}
+
+impl<'a> Components<'a> {
+    #[inline]
+    pub(crate) fn new(path: &'a [u8]) -> Components<'a> {
+        let prefix = parse_prefix(path);
+
+        let has_physical_root = match path.get(prefix.map(RawPrefix::len).unwrap_or(0)) {
+            Some(&b) => is_sep_byte(b),
+            None => false,
+        };
+
+        Components {
+            path,
+            prefix,
+            has_physical_root,
+            state: State::Prefix,
+        }
+    }
+
+    /// Whether the path has a root, which is either a separator after the prefix or implied by the prefix.
+    #[inline]
+    pub(crate) fn has_root(&self) -> bool {
+        self.has_physical_root || self.prefix.map(RawPrefix::has_implicit_root).unwrap_or(false)
+    }
+
+    /// Whether the path has both a prefix and a root, like `Path::is_absolute` on Windows.
+    #[inline]
+    pub(crate) fn is_absolute(&self) -> bool {
+        self.prefix.is_some() && self.has_root()
+    }
+
+    #[inline]
+    pub(crate) fn has_prefix(&self) -> bool {
+        self.prefix.is_some()
+    }
+
+    /// The bytes after the components which have been iterated, including the separator after the last one.
+    #[inline]
+    pub(crate) fn remaining(&self) -> &'a [u8] {
+        self.path
+    }
+
+    /// Whether the path has a verbatim prefix, after which only `\` is a separator.
+    #[inline]
+    pub(crate) fn is_verbatim(&self) -> bool {
+        self.prefix.map(RawPrefix::is_verbatim).unwrap_or(false)
+    }
+
+    #[inline]
+    fn is_sep(&self, b: u8) -> bool {
+        if self.is_verbatim() {
+            b == b'\\'
+        } else {
+            is_sep_byte(b)
+        }
+    }
+
+    /// Whether a leading **Single Dot** is kept as a component. It can only happen when there is no prefix and no root.
+    #[inline]
+    fn include_cur_dir(&self) -> bool {
+        match self.path {
+            [b'.'] => true,
+            [b'.', b, ..] => self.is_sep(*b),
+            _ => false,
+        }
+    }
+
+    #[inline]
+    fn parse_single_component(&self, token: &'a [u8]) -> Option<Component<'a>> {
+        match token {
+            b"." if self.prefix.map(RawPrefix::is_verbatim).unwrap_or(false) => {
+                Some(Component::CurDir)
+            },
+            b"." | b"" => None,
+            b".." => Some(Component::ParentDir),
+            _ => Some(Component::Normal(token)),
+        }
+    }
+}
+
+impl<'a> Iterator for Components<'a> {
+    type Item = Component<'a>;
+
+    fn next(&mut self) -> Option<Component<'a>> {
+        loop {
+            match self.state {
+                State::Prefix => {
+                    self.state = State::StartDir;
+
+                    if let Some(prefix) = self.prefix {
+                        let (prefix, path) = self.path.split_at(prefix.len());
+
+                        self.path = path;
+
+                        return Some(Component::Prefix(prefix));
+                    }
+                },
+                State::StartDir => {
+                    self.state = State::Body;
+
+                    if self.has_physical_root {
+                        self.path = &self.path[1..];
+
+                        return Some(Component::RootDir);
+                    } else if let Some(prefix) = self.prefix {
+                        if prefix.has_implicit_root() && !prefix.is_verbatim() {
+                            return Some(Component::RootDir);
+                        }
+                    } else if self.include_cur_dir() {
+                        self.path = &self.path[1..];
+
+                        return Some(Component::CurDir);
+                    }
+                },
+                State::Body => {
+                    if self.path.is_empty() {
+                        self.state = State::Done;
+
+                        continue;
+                    }
+
+                    let (token, size) = match self.path.iter().position(|&b| self.is_sep(b)) {
+                        Some(index) => (&self.path[..index], index + 1),
+                        None => (self.path, self.path.len()),
+                    };
+
+                    self.path = &self.path[size..];
+
+                    if let Some(component) = self.parse_single_component(token) {
+                        return Some(component);
+                    }
+                },
+                State::Done => return None,
+            }
+        }
+    }
+}
+
+/// The components of the parent of a Windows path, like `Path::parent` on Windows.
+pub(crate) fn parent(path: &[u8]) -> Option<impl Iterator<Item = Component<'_>>> {
+    let count = Components::new(path).count();
+
+    match Components::new(path).last() {
+        Some(Component::Normal(_)) | Some(Component::CurDir) | Some(Component::ParentDir) => {
+            Some(Components::new(path).take(count - 1))
+        },
+        _ => None,
+    }
+}
+
+/// Whether a Windows path is just a root, like `path == Path::new("\\")` on Windows.
+#[inline]
+pub(crate) fn is_root(path: &[u8]) -> bool {
+    let mut iter = Components::new(path);
+
+    iter.next() == Some(Component::RootDir) && iter.next().is_none()
+}
+
+/// Parse the prefix of a Windows path in the same way as `std::path::Path::components` on Windows.
+pub(crate) fn parse_prefix(path: &[u8]) -> Option<RawPrefix<'_>> {
+    // only the first 8 bytes are needed to recognize the kind of the prefix, and `/` is treated as `\`
+    let mut head = [0u8; 8];
+
+    let head_length = path.len().min(head.len());
+
+    for (h, &b) in head.iter_mut().zip(path.iter()) {
+        *h = if b == b'/' { b'\\' } else { b };
+    }
+
+    let head = &head[..head_length];
+
+    if head.starts_with(br"\\") {
+        // verbatim paths must use `\` in their leading `\\?\`
+        if head[2..].starts_with(br"?\") && !path[..4].contains(&b'/') {
+            if head[4..].starts_with(br"UNC\") {
+                let (server, path) = parse_next_component(&path[8..], true);
+                let (share, _) = parse_next_component(path, true);
+
+                Some(RawPrefix::VerbatimUNC(server, share))
+            } else {
+                let path = &path[4..];
+
+                match parse_drive_exact(path) {
+                    Some(drive) => Some(RawPrefix::VerbatimDisk(drive)),
+                    None => {
+                        let (prefix, _) = parse_next_component(path, true);
+
+                        Some(RawPrefix::Verbatim(prefix))
+                    },
+                }
+            }
+        } else if head[2..].starts_with(br".\") {
+            let (prefix, _) = parse_next_component(&path[4..], false);
+
+            Some(RawPrefix::DeviceNS(prefix))
+        } else {
+            let (server, path) = parse_next_component(&path[2..], false);
+            let (share, _) = parse_next_component(path, false);
+
+            if !server.is_empty() && !share.is_empty() {
+                Some(RawPrefix::UNC(server, share))
+            } else {
+                None
+            }
+        }
+    } else {
+        parse_drive(path).map(RawPrefix::Disk)
+    }
+}
+
+#[inline]
+fn is_sep_byte(b: u8) -> bool {
+    b == b'\\' || b == b'/'
+}
+
+#[inline]
+fn parse_drive(path: &[u8]) -> Option<u8> {
+    match path {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => Some(drive.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+#[inline]
+fn parse_drive_exact(path: &[u8]) -> Option<u8> {
+    match path.get(2) {
+        Some(&b) if b != b'\\' => None,
+        _ => parse_drive(path),
+    }
+}
+
+#[inline]
+fn parse_next_component(path: &[u8], verbatim: bool) -> (&[u8], &[u8]) {
+    let is_sep = |b: &u8| if verbatim { *b == b'\\' } else { is_sep_byte(*b) };
+
+    match path.iter().position(is_sep) {
+        Some(index) => (&path[..index], &path[index + 1..]),
+        None => (path, &[]),
+    }
+}
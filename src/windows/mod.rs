@@ -0,0 +1,624 @@
+/*!
+Dedot paths with the Windows syntax on any host.
+
+The functions in this module work on `str`, `OsStr` or `[u8]` instead of `Path`, and parse prefixes (`C:`, `\\server\share`, `\\?\`, ...), separators and dots in the same way as `std::path` on Windows. They give the same results as the `ParseDot` and `Clean` implementations on Windows, which are built on them, so a Windows path received by a Linux server can be normalized as well.
+
+The `expand_tilde` option is ignored, because the home directory of the host is not a Windows path.
+*/
+
+mod components;
+#[cfg(windows)]
+mod prefix;
+
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    io,
+    path::{PathBuf, Prefix},
+};
+
+use components::{Component, Components, RawPrefix};
+#[cfg(windows)]
+pub use prefix::ParsePrefix;
+
+use crate::{
+    path_str::from_bytes_cow,
+    tokens::{dedot_tail_in_place, JoinedPath, TokenSink},
+    DedotOptions, EscapePolicy, PathStr,
+};
+
+/// The separator of Windows paths.
+const SEPARATOR: &[u8] = br"\";
+
+/// Parse the prefix of a Windows path, like the `Prefix` component of `std::path::Path` on Windows.
+///
+/// ```
+/// use std::{ffi::OsStr, path::Prefix};
+///
+/// use path_dedot::windows;
+///
+/// assert_eq!(Some(Prefix::Disk(b'C')), windows::parse_prefix(r"c:\path"));
+/// assert_eq!(
+///     Some(Prefix::UNC(OsStr::new("server"), OsStr::new("share"))),
+///     windows::parse_prefix(r"\\server\share\path")
+/// );
+/// assert_eq!(None, windows::parse_prefix(r"\path"));
+/// ```
+#[inline]
+pub fn parse_prefix<S: AsRef<OsStr> + ?Sized>(path: &S) -> Option<Prefix<'_>> {
+    let prefix = components::parse_prefix(path.as_ref().as_encoded_bytes())?;
+
+    // SAFETY: the prefix is parsed from the bytes of an `OsStr`
+    Some(unsafe { prefix.to_prefix() })
+}
+
+/// Make a Windows path absolute with a given current working directory and remove dots in the path, like the `parse_dot_from` method on Windows.
+///
+/// ```
+/// use path_dedot::windows;
+///
+/// assert_eq!(
+///     r"C:\path\to\777",
+///     windows::parse_dot_from(r"C:\path\to\123\..\777", r"C:\").unwrap()
+/// );
+/// assert_eq!(
+///     r"C:\cwd\path",
+///     windows::parse_dot_from(r".\path", r"C:\cwd").unwrap()
+/// );
+/// ```
+#[inline]
+pub fn parse_dot_from<'a, S: PathStr + ?Sized>(path: &'a S, cwd: &S) -> io::Result<Cow<'a, S>> {
+    parse_dot_from_with(path, cwd, DedotOptions::new())
+}
+
+/// Make a Windows path absolute with a given current working directory and remove dots in the path with options, like the `parse_dot_from_with` method on Windows. The `expand_tilde` option is ignored.
+#[inline]
+pub fn parse_dot_from_with<'a, S: PathStr + ?Sized>(
+    path: &'a S,
+    cwd: &S,
+    options: DedotOptions,
+) -> io::Result<Cow<'a, S>> {
+    let dedotted =
+        parse_dot_bytes(path.as_bytes(), cwd.as_bytes(), options, &|| path.to_path_buf())?;
+
+    Ok(from_bytes_cow(path, dedotted))
+}
+
+/// Check whether a Windows path is already dedotted, which means `parse_dot_from` returns it as it is, like the `is_dedotted` method on Windows. The current working directory is not needed.
+///
+/// ```
+/// use path_dedot::windows;
+///
+/// assert!(windows::is_dedotted(r"C:\path\to\123"));
+/// assert!(windows::is_dedotted("C:/path/to/123"));
+/// assert!(!windows::is_dedotted(r"C:\path\..\123"));
+/// assert!(!windows::is_dedotted(r"C:\path\\123\"));
+/// ```
+#[inline]
+pub fn is_dedotted<S: PathStr + ?Sized>(path: &S) -> bool {
+    is_dedotted_bytes(path.as_bytes())
+}
+
+/// Check whether `parse_dot_from` uses the current working directory for a Windows path, which is only the case when the path starts with a **Single Dot** or **Double Dots**, possibly after a prefix, like the `needs_cwd` method on Windows.
+///
+/// ```
+/// use path_dedot::windows;
+///
+/// assert!(windows::needs_cwd(r".\path"));
+/// assert!(windows::needs_cwd(r"C:..\path"));
+/// assert!(windows::needs_cwd(r"C:.\path"));
+/// assert!(!windows::needs_cwd(r"C:\path\..\123"));
+/// assert!(!windows::needs_cwd(r"\path\..\123"));
+/// ```
+#[inline]
+pub fn needs_cwd<S: PathStr + ?Sized>(path: &S) -> bool {
+    needs_cwd_bytes(path.as_bytes())
+}
+
+/// Remove dots in the bytes of a Windows path by rewriting the buffer, like the `dedot_in_place_from` method on Windows. Return whether the path is changed.
+///
+/// ```
+/// use path_dedot::windows;
+///
+/// let mut path = br"C:\path\to\123\..\777".to_vec();
+///
+/// assert!(windows::dedot_in_place_from(&mut path, br"C:\").unwrap());
+/// assert_eq!(br"C:\path\to\777", path.as_slice());
+///
+/// let mut path = br".\path".to_vec();
+///
+/// assert!(windows::dedot_in_place_from(&mut path, br"C:\cwd").unwrap());
+/// assert_eq!(br"C:\cwd\path", path.as_slice());
+/// ```
+#[inline]
+pub fn dedot_in_place_from(path: &mut Vec<u8>, cwd: &[u8]) -> io::Result<bool> {
+    dedot_in_place_bytes(path, cwd)
+}
+
+/// Lexically remove dots in a Windows path without the current working directory, like the `clean` method on Windows.
+///
+/// ```
+/// use path_dedot::windows;
+///
+/// assert_eq!(r"..\123", windows::clean(r"path\to\..\..\..\123"));
+/// assert_eq!(
+///     r"\\server\share\123",
+///     windows::clean(r"\\server\share\path\..\..\123")
+/// );
+/// ```
+#[inline]
+pub fn clean<S: PathStr + ?Sized>(path: &S) -> Cow<'_, S> {
+    from_bytes_cow(path, clean_bytes(path.as_bytes()))
+}
+
+/// Push the components of `cwd` into `sink`. If the path already has a prefix, the prefix of `cwd` is skipped.
+#[inline]
+fn push_cwd<'a>(
+    sink: &mut impl TokenSink<'a>,
+    cwd: impl Iterator<Item = Component<'a>>,
+    cwd_has_prefix: bool,
+    has_prefix: bool,
+) {
+    for component in cwd.skip(usize::from(has_prefix && cwd_has_prefix)) {
+        match component {
+            Component::Prefix(prefix) => sink.push_prefix(prefix),
+            Component::RootDir => sink.push_root(),
+            _ => sink.push(component.as_bytes()),
+        }
+    }
+}
+
+/// The implementation of `parse_dot_from_with`. `to_path_buf` creates the path for an `EscapeError`. The result is borrowed only if it is `path` itself.
+pub(crate) fn parse_dot_bytes<'a>(
+    path: &'a [u8],
+    cwd: &[u8],
+    options: DedotOptions,
+    to_path_buf: &dyn Fn() -> PathBuf,
+) -> io::Result<Cow<'a, [u8]>> {
+    let trailing_separator = options.keep_trailing_separator && ends_with_directory(path);
+
+    let mut joined = JoinedPath::new(path, SEPARATOR[0]);
+
+    match dedot(path, cwd, options.escape_policy, to_path_buf, &mut joined)? {
+        Some(has_dots) => Ok(joined.into_cow(has_dots, trailing_separator)),
+        None => Ok(Cow::from(path)),
+    }
+}
+
+/// Remove dots in the path in a single pass, and push the tokens of the result into `sink`. Return whether any component is removed or ignored, or `None` if the path has no components.
+pub(crate) fn dedot<'a>(
+    path: &'a [u8],
+    cwd: &'a [u8],
+    escape_policy: EscapePolicy,
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+) -> io::Result<Option<bool>> {
+    let mut iter = Components::new(path);
+
+    let mut has_dots = false;
+
+    let cwd_components = Components::new(cwd);
+
+    let cwd_has_prefix = cwd_components.has_prefix();
+
+    if let Some(first_component) = iter.next() {
+        match first_component {
+            Component::Prefix(prefix) => {
+                sink.push_prefix(prefix);
+
+                if let Some(second_component) = iter.next() {
+                    match second_component {
+                        Component::RootDir => sink.push_root(),
+                        Component::CurDir => {
+                            // only for verbatim paths
+                            has_dots = true;
+
+                            push_cwd(sink, cwd_components, cwd_has_prefix, true);
+                        },
+                        Component::ParentDir => {
+                            has_dots = true;
+
+                            match components::parent(cwd) {
+                                Some(cwd_parent) => {
+                                    push_cwd(sink, cwd_parent, cwd_has_prefix, true)
+                                },
+                                None => {
+                                    let rooted = if cwd_has_prefix {
+                                        cwd_components.is_absolute()
+                                    } else {
+                                        // don't care about `cwd` is "\\" or "\\\"
+                                        components::is_root(cwd)
+                                    };
+
+                                    if rooted {
+                                        sink.push_root();
+                                    }
+
+                                    escape_policy.escape(to_path_buf, 1, sink, rooted)?;
+                                },
+                            }
+                        },
+                        _ => {
+                            // the Single Dot right after a disk prefix, e.g. `C:.\path`, is not a component
+                            if path[prefix.len()..].starts_with(br".\") {
+                                has_dots = true;
+
+                                push_cwd(sink, cwd_components, cwd_has_prefix, true);
+                            }
+
+                            sink.push(second_component.as_bytes());
+                        },
+                    }
+                }
+            },
+            Component::RootDir => sink.push_root(),
+            Component::CurDir => {
+                has_dots = true;
+
+                push_cwd(sink, cwd_components, cwd_has_prefix, false);
+            },
+            Component::ParentDir => {
+                has_dots = true;
+
+                match components::parent(cwd) {
+                    Some(cwd_parent) => push_cwd(sink, cwd_parent, cwd_has_prefix, false),
+                    None => {
+                        let rooted = match cwd_components.clone().next() {
+                            Some(Component::Prefix(prefix)) => {
+                                sink.push_prefix(prefix);
+
+                                cwd_components.is_absolute()
+                            },
+                            _ => {
+                                // don't care about `cwd` is "\\" or "\\\"
+                                components::is_root(cwd)
+                            },
+                        };
+
+                        if rooted {
+                            sink.push_root();
+                        }
+
+                        escape_policy.escape(to_path_buf, 0, sink, rooted)?;
+                    },
+                }
+            },
+            Component::Normal(token) => sink.push(token),
+        }
+
+        let start_index = if let Component::Prefix(_) = first_component { 2 } else { 1 };
+
+        if push_components(to_path_buf, sink, iter, start_index, escape_policy)? {
+            has_dots = true;
+        }
+
+        Ok(Some(has_dots))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The implementation of `is_dedotted`. Without dots, the tokens pushed by `dedot` are exactly the components of the path, so only the length of the joined path needs to be checked.
+pub(crate) fn is_dedotted_bytes(path: &[u8]) -> bool {
+    let iter = Components::new(path);
+
+    let has_prefix = iter.has_prefix();
+
+    let mut prefix_length = 0;
+    let mut has_root = false;
+
+    let mut size = 0;
+    let mut count = 0;
+
+    for component in iter {
+        match component {
+            Component::Prefix(prefix) => prefix_length = prefix.len(),
+            Component::RootDir => has_root = true,
+            Component::CurDir | Component::ParentDir => return false,
+            Component::Normal(token) => {
+                // the Single Dot right after a disk prefix, e.g. `C:.\path`, is not a component
+                if count == 0
+                    && has_prefix
+                    && !has_root
+                    && path[prefix_length..].starts_with(br".\")
+                {
+                    return false;
+                }
+
+                size += token.len();
+                count += 1;
+            },
+        }
+    }
+
+    if has_prefix && has_root && count == 0 && prefix_length == path.len() {
+        // `\\server\share`
+        return true;
+    }
+
+    // the same as `JoinedPath`
+    prefix_length + usize::from(has_root) + size + count.max(1) - 1 == path.len()
+}
+
+/// The implementation of `needs_cwd`. `dedot` only uses `cwd` for the first component, or the second one after a prefix.
+pub(crate) fn needs_cwd_bytes(path: &[u8]) -> bool {
+    let mut iter = Components::new(path);
+
+    match iter.next() {
+        Some(Component::Prefix(prefix)) => match iter.next() {
+            Some(Component::CurDir | Component::ParentDir) => true,
+            // the Single Dot right after a disk prefix, e.g. `C:.\path`, is not a component
+            Some(Component::Normal(_)) => path[prefix.len()..].starts_with(br".\"),
+            _ => false,
+        },
+        Some(Component::CurDir | Component::ParentDir) => true,
+        _ => false,
+    }
+}
+
+/// The implementation of `dedot_in_place_from`, which gives the same result as `parse_dot_bytes` with the default options. The buffer only grows when a leading **Single Dot** or **Double Dots** is replaced by `cwd`. Return whether the buffer is changed.
+pub(crate) fn dedot_in_place_bytes(buffer: &mut Vec<u8>, cwd: &[u8]) -> io::Result<bool> {
+    if is_dedotted_bytes(buffer) {
+        return Ok(false);
+    }
+
+    let mut iter = Components::new(buffer);
+
+    let verbatim = iter.is_verbatim();
+
+    let is_separator = move |b: u8| b == SEPARATOR[0] || (!verbatim && b == b'/');
+
+    // the root is always physical here, because a path which is just a prefix is already dedotted
+    let dedot_after_root = |buffer: &mut Vec<u8>, root_index: usize| {
+        let changed = buffer[root_index] != SEPARATOR[0];
+
+        buffer[root_index] = SEPARATOR[0];
+
+        dedot_tail_in_place(buffer, root_index + 1, root_index + 1, SEPARATOR[0], is_separator)
+            || changed
+    };
+
+    // the end of the last iterated component, without the separator after it
+    let token_end = |iter: &Components| {
+        let end = buffer.len() - iter.remaining().len();
+
+        if is_separator(buffer[end - 1]) {
+            end - 1
+        } else {
+            end
+        }
+    };
+
+    let head_end = match iter.next() {
+        Some(Component::Prefix(prefix)) => {
+            let prefix_length = prefix.len();
+
+            match iter.next() {
+                Some(Component::RootDir) => return Ok(dedot_after_root(buffer, prefix_length)),
+                // the Single Dot right after a disk prefix, e.g. `C:.\path`, is replaced with the CWD as well
+                Some(Component::CurDir) | Some(Component::ParentDir) => token_end(&iter),
+                Some(Component::Normal(_)) if buffer[prefix_length..].starts_with(br".\") => {
+                    token_end(&iter)
+                },
+                _ => {
+                    return Ok(dedot_tail_in_place(
+                        buffer,
+                        prefix_length,
+                        prefix_length,
+                        SEPARATOR[0],
+                        is_separator,
+                    ))
+                },
+            }
+        },
+        Some(Component::RootDir) => return Ok(dedot_after_root(buffer, 0)),
+        Some(Component::CurDir) => 1,
+        Some(Component::ParentDir) => 2,
+        _ => return Ok(dedot_tail_in_place(buffer, 0, 0, SEPARATOR[0], is_separator)),
+    };
+
+    // replace the leading Single Dot or Double Dots with the CWD or its parent
+    let head =
+        parse_dot_bytes(&buffer[..head_end], cwd, DedotOptions::new(), &PathBuf::new)?.into_owned();
+
+    let changed = buffer[..head_end] != head[..];
+
+    let start = head.len();
+    let prefix_length = components::parse_prefix(&head).map(RawPrefix::len).unwrap_or(0);
+    let base = prefix_length + usize::from(head.get(prefix_length) == Some(&SEPARATOR[0]));
+
+    buffer.splice(..head_end, head);
+
+    Ok(dedot_tail_in_place(buffer, start, base, SEPARATOR[0], is_separator) || changed)
+}
+
+/// The implementation of `clean`. The result is borrowed only if it is `path` itself.
+pub(crate) fn clean_bytes(path: &[u8]) -> Cow<'_, [u8]> {
+    let mut iter = Components::new(path);
+
+    let mut has_dots = false;
+
+    if let Some(first_component) = iter.next() {
+        let mut joined = JoinedPath::new(path, SEPARATOR[0]);
+
+        let mut start_index = 1;
+
+        let anchored = matches!(first_component, Component::Prefix(_) | Component::RootDir);
+
+        match first_component {
+            Component::Prefix(prefix) => {
+                joined.push_prefix(prefix);
+
+                match iter.clone().next() {
+                    Some(Component::RootDir) => {
+                        iter.next();
+
+                        start_index = 2;
+
+                        joined.push_root();
+                    },
+                    Some(Component::CurDir) => {
+                        iter.next();
+
+                        start_index = 2;
+
+                        has_dots = true;
+                    },
+                    _ => (),
+                }
+            },
+            Component::RootDir => joined.push_root(),
+            Component::CurDir => has_dots = true,
+            _ => joined.push(first_component.as_bytes()),
+        }
+
+        // `EscapePolicy::Preserve` never fails
+        if push_components(&PathBuf::new, &mut joined, iter, start_index, EscapePolicy::Preserve)
+            .unwrap_or(true)
+        {
+            has_dots = true;
+        }
+
+        if joined.is_empty() {
+            return if path == b"." { Cow::from(path) } else { Cow::from(b".".to_vec()) };
+        }
+
+        let cleaned = joined.into_cow(has_dots, false);
+
+        // like Go's `filepath.Clean`, a name which has become the first one, e.g. `C:` in `.\C:\x`, must not be parsed as a prefix
+        if !anchored && components::parse_prefix(&cleaned).is_some() {
+            let mut guarded = br".\".to_vec();
+
+            guarded.extend_from_slice(&cleaned);
+
+            return if guarded == path { Cow::from(path) } else { Cow::from(guarded) };
+        }
+
+        cleaned
+    } else {
+        // like an empty result
+        Cow::from(b".".to_vec())
+    }
+}
+
+/// Push the remaining components of `path`, starting at `start_index`, into `sink` and let **Double Dots** pop the last token. Return `true` if any component is removed or ignored.
+fn push_components<'a>(
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+    iter: Components<'a>,
+    start_index: usize,
+    escape_policy: EscapePolicy,
+) -> io::Result<bool> {
+    let mut has_dots = false;
+
+    for (index, component) in iter.enumerate().map(|(i, c)| (i + start_index, c)) {
+        match component {
+            Component::CurDir => {
+                // only for verbatim paths
+                has_dots = true;
+            },
+            Component::ParentDir => {
+                if pop_parent_dir(to_path_buf, sink, index, escape_policy)? {
+                    has_dots = true;
+                }
+            },
+            _ => sink.push(component.as_bytes()),
+        }
+    }
+
+    Ok(has_dots)
+}
+
+/// Let the **Double Dots** at `index` pop the last token, or handle it with `escape_policy` if it cannot. Return `true` if the **Double Dots** is removed or ignored.
+fn pop_parent_dir<'a>(
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+    index: usize,
+    escape_policy: EscapePolicy,
+) -> io::Result<bool> {
+    match sink.last() {
+        // the prefix and the root cannot be popped
+        Some(last) if escape_policy != EscapePolicy::Preserve || last != b".." => {
+            sink.pop();
+
+            Ok(true)
+        },
+        _ => {
+            let rooted = sink.has_root();
+
+            escape_policy.escape(to_path_buf, index, sink, rooted)?;
+
+            Ok(escape_policy != EscapePolicy::Preserve || rooted)
+        },
+    }
+}
+
+/// The implementation of `join_confined`. The tokens are pushed into `sink` as if the path were relative to the root of `sink`, which must have been pushed. `escape_policy` must not be `EscapePolicy::Preserve`.
+///
+/// A prefix or a root escapes in the same way as the **Double Dots** climbing above the root of `sink`, wherever it is. A name which is only made of dots and spaces is treated as **Double Dots**, because Windows removes the trailing dots and spaces of a name. A token which is not a single name when it is parsed again under the root, e.g. `a/..` after a verbatim prefix or `C:x`, is dedotted again with the index of the whole token.
+#[cfg(windows)]
+pub(crate) fn dedot_confined<'a>(
+    path: &'a [u8],
+    escape_policy: EscapePolicy,
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+) -> io::Result<()> {
+    push_confined(to_path_buf, sink, path, None, escape_policy)
+}
+
+#[cfg(windows)]
+fn push_confined<'a>(
+    to_path_buf: &dyn Fn() -> PathBuf,
+    sink: &mut impl TokenSink<'a>,
+    path: &'a [u8],
+    token_index: Option<usize>,
+    escape_policy: EscapePolicy,
+) -> io::Result<()> {
+    for (i, component) in Components::new(path).enumerate() {
+        let index = token_index.unwrap_or(i);
+
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                escape_policy.escape(to_path_buf, index, sink, true)?;
+            },
+            Component::CurDir => (),
+            Component::ParentDir => {
+                pop_parent_dir(to_path_buf, sink, index, escape_policy)?;
+            },
+            Component::Normal(token) if token.iter().all(|&b| b == b'.' || b == b' ') => {
+                pop_parent_dir(to_path_buf, sink, index, escape_policy)?;
+            },
+            Component::Normal(token) => {
+                let mut inner = Components::new(token);
+
+                if inner.next() == Some(component) && inner.next().is_none() {
+                    sink.push(token);
+                } else {
+                    push_confined(to_path_buf, sink, token, Some(index), escape_policy)?;
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a Windows path ends with a separator, a **Single Dot** or **Double Dots**, which means it is a directory.
+pub(crate) fn ends_with_directory(path: &[u8]) -> bool {
+    let path = &path[components::parse_prefix(path).map(|prefix| prefix.len()).unwrap_or(0)..];
+
+    let is_separator = |b: &u8| *b == b'\\' || *b == b'/';
+
+    match path.last() {
+        Some(b) if is_separator(b) => true,
+        Some(_) => {
+            let segment = match path.iter().rposition(is_separator) {
+                Some(index) => &path[index + 1..],
+                None => path,
+            };
+
+            segment == b"." || segment == b".."
+        },
+        None => false,
+    }
+}
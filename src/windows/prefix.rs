@@ -0,0 +1,22 @@
+use std::path::{Component, Path, PathBuf, PrefixComponent};
+
+pub trait ParsePrefix {
+    fn get_path_prefix(&self) -> Option<PrefixComponent<'_>>;
+}
+
+impl ParsePrefix for Path {
+    #[inline]
+    fn get_path_prefix(&self) -> Option<PrefixComponent<'_>> {
+        match self.components().next() {
+            Some(Component::Prefix(prefix_component)) => Some(prefix_component),
+            _ => None,
+        }
+    }
+}
+
+impl ParsePrefix for PathBuf {
+    #[inline]
+    fn get_path_prefix(&self) -> Option<PrefixComponent<'_>> {
+        self.as_path().get_path_prefix()
+    }
+}
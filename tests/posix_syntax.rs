@@ -0,0 +1,218 @@
+use std::{borrow::Cow, ffi::OsStr, path::Path};
+
+use path_dedot::{posix, DedotError, DedotOptions, EscapePolicy};
+
+#[test]
+fn dedot_lv1() {
+    assert_eq!(
+        "/path/to/123/456/777",
+        posix::parse_dot_from("/path/to/123/456/./777", "/").unwrap()
+    );
+    assert_eq!("/path/to/123/777", posix::parse_dot_from("/path/to/123/456/../777", "/").unwrap());
+    assert_eq!(
+        "/path/123/456/777",
+        posix::parse_dot_from("/path/to/../123/456/./777", "/").unwrap()
+    );
+    assert_eq!(
+        "/path/123/456",
+        posix::parse_dot_from("/path/to/../123/456/./777/..", "/").unwrap()
+    );
+}
+
+#[test]
+fn dedot_lv2() {
+    assert_eq!("path/123/456", posix::parse_dot_from("path/to/../123/456/./777/..", "/").unwrap());
+    assert_eq!(
+        "123/456",
+        posix::parse_dot_from("path/to/../../../../123/456/./777/..", "/").unwrap()
+    );
+    assert_eq!(
+        "/123/456",
+        posix::parse_dot_from("/path/to/../../../../123/456/./777/..", "/").unwrap()
+    );
+}
+
+#[test]
+fn dedot_lv3() {
+    for cwd in ["/foo/bar/baz", "foo/bar/baz"] {
+        assert_eq!("/", posix::parse_dot_from("/", cwd).unwrap());
+        assert_eq!("", posix::parse_dot_from("", cwd).unwrap());
+        assert_eq!("abc", posix::parse_dot_from("abc", cwd).unwrap());
+    }
+}
+
+#[test]
+fn dedot_lv4() {
+    for p in ["./abc", "../abc"] {
+        assert_eq!("/abc", posix::parse_dot_from(p, "/").unwrap());
+        assert_eq!("abc", posix::parse_dot_from(p, "").unwrap());
+    }
+
+    assert_eq!("/foo/bar/baz/abc", posix::parse_dot_from("./abc", "/foo/bar/baz").unwrap());
+    assert_eq!("foo/bar/baz/abc", posix::parse_dot_from("./abc", "foo/bar/baz").unwrap());
+    assert_eq!("/foo/bar/abc", posix::parse_dot_from("../abc", "/foo/bar/baz").unwrap());
+    assert_eq!("foo/bar/abc", posix::parse_dot_from("../abc", "foo/bar/baz").unwrap());
+}
+
+#[test]
+fn dedot_separator() {
+    // `\` and prefixes are ordinary characters
+    assert_eq!(r"C:\path\..", posix::parse_dot_from(r"C:\path\..", "/").unwrap());
+    assert_eq!(r"/cwd/C:\path", posix::parse_dot_from(r"./C:\path", "/cwd").unwrap());
+    assert_eq!("/path/777", posix::parse_dot_from("//path//to/..///777", "/").unwrap());
+}
+
+#[test]
+fn borrowed() {
+    for p in ["/", "", "abc", "/path/to/123", "path/to/123"] {
+        assert!(matches!(posix::parse_dot_from(p, "/cwd").unwrap(), Cow::Borrowed(_)), "{}", p);
+
+        if !p.is_empty() {
+            assert!(matches!(posix::clean(p), Cow::Borrowed(_)), "{}", p);
+        }
+    }
+}
+
+#[test]
+fn clean() {
+    assert_eq!(".", posix::clean(""));
+    assert_eq!(".", posix::clean("."));
+    assert_eq!("/", posix::clean("/"));
+    assert_eq!("..", posix::clean(".."));
+    assert_eq!("path/to/123/456", posix::clean("./path/to/123/456"));
+    assert_eq!("../../123/456", posix::clean("path/to/../../../../123/456/./777/.."));
+    assert_eq!("/123/456", posix::clean("/path/to/../../../../123/456/./777/.."));
+    assert_eq!(".", posix::clean("path/.."));
+
+    // nothing is changed
+    assert!(matches!(posix::clean("."), Cow::Borrowed(_)));
+    assert!(matches!(posix::clean("./"), Cow::Owned(_)));
+}
+
+#[test]
+fn escape_policy() {
+    let parse = |p, cwd, escape_policy| {
+        posix::parse_dot_from_with(p, cwd, DedotOptions::new().escape_policy(escape_policy))
+    };
+
+    assert_eq!("123", parse("path/../../123", "/foo", EscapePolicy::Clamp).unwrap());
+    assert_eq!("../123", parse("path/../../123", "/foo", EscapePolicy::Preserve).unwrap());
+
+    for (p, cwd, index) in
+        [("path/../../123", "/foo", 2), ("/path/../../123", "/foo", 3), ("../abc", "/", 0)]
+    {
+        let error = parse(p, cwd, EscapePolicy::Error).unwrap_err();
+
+        let error = DedotError::downcast_ref(&error).unwrap();
+
+        assert_eq!(Path::new(p), error.path());
+        assert_eq!(index, error.index());
+    }
+}
+
+#[test]
+fn trailing_separator() {
+    let parse = |p, cwd| {
+        posix::parse_dot_from_with(p, cwd, DedotOptions::new().keep_trailing_separator(true))
+    };
+
+    assert_eq!("dir/", parse("dir/sub/..", "/foo").unwrap());
+    assert_eq!("/foo/", parse("./", "/foo").unwrap());
+    assert_eq!("/", parse("/dir/..", "/foo").unwrap());
+}
+
+#[test]
+fn bytes() {
+    let p: &[u8] = b"/path/to/\xFF/../777";
+
+    assert_eq!(b"/path/to/777", &*posix::parse_dot_from(p, b"/".as_slice()).unwrap());
+    assert_eq!(b"/path/to/777", &*posix::clean(p));
+    assert_eq!(
+        b"/cwd/\xFF",
+        &*posix::parse_dot_from(b"./\xFF".as_slice(), b"/cwd".as_slice()).unwrap()
+    );
+}
+
+#[test]
+fn os_str() {
+    let p = OsStr::new("/path/to/../777");
+
+    assert_eq!(OsStr::new("/path/777"), posix::parse_dot_from(p, OsStr::new("/")).unwrap());
+    assert_eq!(OsStr::new("/path/777"), posix::clean(p));
+}
+
+#[test]
+fn is_dedotted() {
+    for p in ["/path/to/123", "path/to/123", "/", "", "abc", ".abc", "..abc"] {
+        assert!(posix::is_dedotted(p), "{}", p);
+    }
+
+    for p in ["/path/../123", "/path//123", "/path/", "./path", "path/.", ".", "//"] {
+        assert!(!posix::is_dedotted(p), "{}", p);
+    }
+
+    // every string of up to 7 characters
+    let alphabet = ["a", ".", "/"];
+
+    let mut paths = vec![String::new()];
+
+    for _ in 0..7 {
+        paths = paths
+            .iter()
+            .flat_map(|p| alphabet.iter().map(move |c| format!("{}{}", p, c)))
+            .collect();
+
+        for p in paths.iter().map(String::as_str) {
+            let borrowed = matches!(posix::parse_dot_from(p, "/cwd").unwrap(), Cow::Borrowed(_));
+
+            assert_eq!(borrowed, posix::is_dedotted(p), "{}", p);
+        }
+    }
+}
+
+#[test]
+fn dedot_in_place() {
+    for (p, cwd, expected) in [
+        (r"/path/to/../123/./456/..", r"/cwd", r"/path/123"),
+        (r"../abc", r"/foo/bar", r"/foo/abc"),
+        (r"/path/to/123", r"/", r"/path/to/123"),
+    ] {
+        let mut path = p.as_bytes().to_vec();
+
+        let changed = posix::dedot_in_place_from(&mut path, cwd.as_bytes()).unwrap();
+
+        assert_eq!(expected.as_bytes(), path.as_slice(), "{}", p);
+        assert_eq!(p != expected, changed, "{}", p);
+        assert_eq!(expected, posix::parse_dot_from(p, cwd).unwrap(), "{}", p);
+    }
+}
+
+#[test]
+fn needs_cwd() {
+    for p in [r"./path", r"../path", r".", r"..", r"./../a", r".//a"] {
+        assert!(posix::needs_cwd(p), "{}", p);
+    }
+
+    for p in [r"/path/../123", r"path/../../123", r"", r"/", r".a", r"..a", r"a/./b"] {
+        assert!(!posix::needs_cwd(p), "{}", p);
+    }
+
+    // every string of up to 5 characters, whose result depends on the CWD exactly when it needs the CWD
+    let alphabet = ["a", ".", "/"];
+
+    let mut paths = vec![String::new()];
+
+    for _ in 0..5 {
+        paths = paths
+            .iter()
+            .flat_map(|p| alphabet.iter().map(move |c| format!("{}{}", p, c)))
+            .collect();
+
+        for p in paths.iter().map(String::as_str) {
+            let a = posix::parse_dot_from(p, "/a/b/c/d").unwrap();
+            let b = posix::parse_dot_from(p, "/e/f/g/h").unwrap();
+
+            assert_eq!(a != b, posix::needs_cwd(p), "{}", p);
+        }
+    }
+}
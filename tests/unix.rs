@@ -1,8 +1,27 @@
-#![cfg(all(unix, not(feature = "unsafe_cache")))]
+#![cfg(unix)]
 
-use std::{env, path::Path};
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::HashMap,
+    env,
+    ffi::{OsStr, OsString},
+    fs,
+    io::{self, ErrorKind},
+    os::unix::{
+        ffi::{OsStrExt, OsStringExt},
+        fs::symlink,
+    },
+    path::{Component, Path, PathBuf},
+};
 
-use path_dedot::ParseDot;
+use path_dedot::{
+    expand_tilde, join_confined,
+    url::{self, FileUrlError},
+    CachedCwd, Clean, CwdPath, CwdProvider, DedotError, DedotOptions, Dedotter, DefaultCwd,
+    EscapePolicy, ParseDot, ParseDotFs, ParseDotMut, ProcessCwd, SymlinkPolicy, TildeError,
+    UndefinedVar, VarError, VarExpander, VarSyntax,
+};
 
 #[test]
 fn dedot_lv0_1() {
@@ -144,3 +163,967 @@ fn dedot_lv9_4() {
     assert_eq!("/foo/bar/abc", p.parse_dot_from("/foo/bar/baz").unwrap().to_str().unwrap());
     assert_eq!("foo/bar/abc", p.parse_dot_from("foo/bar/baz").unwrap().to_str().unwrap());
 }
+
+#[test]
+fn clean_lv0() {
+    assert_eq!(".", Path::new("").clean().to_str().unwrap());
+    assert_eq!(".", Path::new(".").clean().to_str().unwrap());
+    assert_eq!("/", Path::new("/").clean().to_str().unwrap());
+    assert_eq!("..", Path::new("..").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_lv1() {
+    assert_eq!("path/to/123/456", Path::new("./path/to/123/456").clean().to_str().unwrap());
+    assert_eq!("../path/to/123/456", Path::new("../path/to/123/456").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_lv2() {
+    assert_eq!(
+        "/path/123/456",
+        Path::new("/path/to/../123/456/./777/..").clean().to_str().unwrap()
+    );
+    assert_eq!("path/123/456", Path::new("path/to/../123/456/./777/..").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_lv3() {
+    assert_eq!(
+        "../../123/456",
+        Path::new("path/to/../../../../123/456/./777/..").clean().to_str().unwrap()
+    );
+    assert_eq!(
+        "/123/456",
+        Path::new("/path/to/../../../../123/456/./777/..").clean().to_str().unwrap()
+    );
+    assert_eq!("../..", Path::new("../a/../..").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_lv4() {
+    assert_eq!(".", Path::new("path/..").clean().to_str().unwrap());
+    assert_eq!(".", Path::new("./").clean().to_str().unwrap());
+    assert_eq!("/", Path::new("/..").clean().to_str().unwrap());
+    assert_eq!("path/to", Path::new("path//to/").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_borrowed() {
+    for p in ["/", "..", "../path", "path/to", "/path/to", "../../path"] {
+        assert!(matches!(Path::new(p).clean(), Cow::Borrowed(_)), "{}", p);
+    }
+}
+
+fn confine(root: &str, untrusted: &str, escape_policy: EscapePolicy) -> io::Result<String> {
+    join_confined(root, untrusted, escape_policy).map(|p| p.to_str().unwrap().to_string())
+}
+
+fn confine_escape_index(root: &str, untrusted: &str) -> usize {
+    let error = confine(root, untrusted, EscapePolicy::Error).unwrap_err();
+
+    let error = DedotError::downcast_ref(&error).unwrap();
+
+    assert_eq!(Path::new(untrusted), error.path());
+
+    error.index()
+}
+
+#[test]
+fn confine_clamp() {
+    assert_eq!("/srv/upload/a/b", confine("/srv/upload", "a/./b", EscapePolicy::Clamp).unwrap());
+    assert_eq!("/srv/upload/b", confine("/srv/upload", "a/../b", EscapePolicy::Clamp).unwrap());
+    assert_eq!("/srv/upload/b", confine("/srv/upload", "../../b", EscapePolicy::Clamp).unwrap());
+    assert_eq!(
+        "/srv/upload/etc/passwd",
+        confine("/srv/upload", "/etc/passwd", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!("/srv/upload/", confine("/srv/upload/", "a/..", EscapePolicy::Clamp).unwrap());
+    assert_eq!("/srv/upload/x", confine("/srv/upload/", "x", EscapePolicy::Clamp).unwrap());
+    assert_eq!("./x", confine("", "../x", EscapePolicy::Clamp).unwrap());
+}
+
+#[test]
+fn confine_error() {
+    assert_eq!("/srv/upload/b", confine("/srv/upload", "a/../b", EscapePolicy::Error).unwrap());
+
+    assert_eq!(0, confine_escape_index("/srv/upload", "../b"));
+    assert_eq!(2, confine_escape_index("/srv/upload", "a/../../b"));
+    assert_eq!(0, confine_escape_index("/srv/upload", "/etc/passwd"));
+}
+
+#[test]
+fn confine_preserve() {
+    assert_eq!(
+        2,
+        confine("/srv", "a/../../b", EscapePolicy::Preserve)
+            .unwrap_err()
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<DedotError>()
+            .unwrap()
+            .index()
+    );
+}
+
+/// A fake CWD which counts how many times it is asked.
+struct CountingCwd {
+    cwd:   PathBuf,
+    count: Cell<usize>,
+}
+
+impl CwdProvider for CountingCwd {
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        self.count.set(self.count.get() + 1);
+
+        Ok(CwdPath::Borrowed(&self.cwd))
+    }
+}
+
+struct NoCwd;
+
+impl CwdProvider for NoCwd {
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        Err(io::Error::new(ErrorKind::NotFound, "no CWD"))
+    }
+}
+
+#[test]
+fn fixed_path() {
+    let options = DedotOptions::new();
+
+    assert_eq!(
+        Path::new("/foo/bar/path"),
+        Path::new("./path").parse_dot_with_provider(Path::new("/foo/bar"), options).unwrap()
+    );
+    assert_eq!(
+        Path::new("/foo/path"),
+        Path::new("../path").parse_dot_with_provider(&PathBuf::from("/foo/bar"), options).unwrap()
+    );
+    assert_eq!(
+        Path::new("/foo/path"),
+        PathBuf::from("../path").parse_dot_with_provider(&Path::new("/foo/bar"), options).unwrap()
+    );
+}
+
+#[test]
+fn custom_provider() {
+    let provider = CountingCwd {
+        cwd: PathBuf::from("/tenant/home"), count: Cell::new(0)
+    };
+
+    let options = DedotOptions::new();
+
+    // the provider is only asked for a leading Single Dot or Double Dots
+    for p in ["/path/../123", "path/../123", ""] {
+        Path::new(p).parse_dot_with_provider(&provider, options).unwrap();
+    }
+
+    assert_eq!(0, provider.count.get());
+
+    assert_eq!(
+        Path::new("/tenant/123"),
+        Path::new("../123").parse_dot_with_provider(&provider, options).unwrap()
+    );
+    assert_eq!(1, provider.count.get());
+
+    let mut path = PathBuf::from("./path/../123");
+
+    assert!(path.dedot_in_place_with_provider(&provider).unwrap());
+    assert_eq!(Path::new("/tenant/home/123"), path);
+    assert_eq!(2, provider.count.get());
+
+    let mut path = OsString::from("../123");
+
+    assert!(path.dedot_in_place_with_provider(&provider).unwrap());
+    assert_eq!("/tenant/123", path);
+    assert_eq!(3, provider.count.get());
+}
+
+#[test]
+fn provider_error() {
+    assert_eq!(
+        Path::new("/path/123"),
+        Path::new("/path/to/../123").parse_dot_with_provider(&NoCwd, DedotOptions::new()).unwrap()
+    );
+
+    let error =
+        Path::new("./path").parse_dot_with_provider(&NoCwd, DedotOptions::new()).unwrap_err();
+
+    assert_eq!(ErrorKind::NotFound, error.kind());
+
+    let error = DedotError::downcast_ref(&error).unwrap();
+
+    assert!(matches!(error, DedotError::CwdUnavailable { .. }));
+    assert_eq!(Path::new("./path"), error.path());
+    assert_eq!(0, error.index());
+
+    assert!(PathBuf::from("../path").dedot_in_place_with_provider(&NoCwd).is_err());
+}
+
+#[test]
+fn process_providers() {
+    let cwd = env::current_dir().unwrap();
+
+    let p = Path::new("./path/to/123");
+
+    for provider in [&DefaultCwd as &dyn CwdProvider, &ProcessCwd, &CachedCwd] {
+        assert_eq!(
+            cwd.join("path/to/123"),
+            p.parse_dot_with_provider(provider, DedotOptions::new()).unwrap()
+        );
+    }
+
+    assert_eq!(
+        p.parse_dot().unwrap(),
+        p.parse_dot_with_provider(&DefaultCwd, DedotOptions::new()).unwrap()
+    );
+}
+
+#[test]
+fn dedot_components() {
+    let p = Path::new("./path/to/../123/./456");
+
+    assert_eq!(
+        vec![
+            Component::RootDir,
+            Component::Normal(OsStr::new("cwd")),
+            Component::Normal(OsStr::new("path")),
+            Component::Normal(OsStr::new("123")),
+            Component::Normal(OsStr::new("456")),
+        ],
+        p.dedot_components(Path::new("/cwd")).unwrap().collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        vec![Component::Normal(OsStr::new("foo")), Component::Normal(OsStr::new("abc"))],
+        Path::new("../abc").dedot_components(Path::new("foo/bar")).unwrap().collect::<Vec<_>>()
+    );
+
+    assert_eq!(0, Path::new("").dedot_components(Path::new("/cwd")).unwrap().count());
+    assert_eq!(0, Path::new("abc/..").dedot_components(Path::new("/cwd")).unwrap().count());
+}
+
+#[test]
+fn same_as_parse_dot_from() {
+    for cwd in ["/", "/foo/bar/baz", "foo/bar/baz", ".", ""] {
+        for p in [
+            "/path/to/123/456/./777/..",
+            "path/to/../../../../123/456",
+            "./abc",
+            "../abc",
+            "../../abc/..",
+            ".",
+            "..",
+            "/",
+            "//path//to/..///777/",
+        ] {
+            let p = Path::new(p);
+            let cwd = Path::new(cwd);
+
+            let components = p.dedot_components(cwd).unwrap();
+
+            assert_eq!(components.len(), p.parse_dot_from(cwd).unwrap().components().count());
+            assert_eq!(
+                p.parse_dot_from(cwd).unwrap(),
+                components.collect::<PathBuf>(),
+                "{:?} {:?}",
+                p,
+                cwd
+            );
+        }
+    }
+}
+
+#[test]
+fn dedot_in_place() {
+    for (p, cwd, expected) in [
+        ("/path/to/123/456/./777", "/", "/path/to/123/456/777"),
+        ("/path/to/../123/456/./777/..", "/", "/path/123/456"),
+        ("path/to/../../../../123/456", "/", "123/456"),
+        ("//path//to/..///777", "/", "/path/777"),
+        ("./abc", "/foo/bar/baz", "/foo/bar/baz/abc"),
+        ("../abc", "/foo/bar/baz", "/foo/bar/abc"),
+        ("../../../../abc", "/foo", "/abc"),
+        ("/path/to/123", "/", "/path/to/123"),
+    ] {
+        let mut path = PathBuf::from(p);
+
+        let changed = path.dedot_in_place_from(cwd).unwrap();
+
+        assert_eq!(PathBuf::from(expected), path, "{}", p);
+        assert_eq!(p != expected, changed, "{}", p);
+
+        let mut path = OsString::from(p);
+
+        assert_eq!(p != expected, path.dedot_in_place_from(cwd).unwrap(), "{}", p);
+        assert_eq!(OsString::from(expected), path, "{}", p);
+    }
+}
+
+#[test]
+fn keep_allocation() {
+    for p in ["/path/to/../123/./456/..", "path//to/.././123/", "/../../a", "/path/to/123"] {
+        let mut path = PathBuf::from(p);
+
+        path.reserve(64);
+
+        let pointer = path.as_os_str().as_encoded_bytes().as_ptr();
+        let capacity = path.capacity();
+
+        path.dedot_in_place_from("/cwd").unwrap();
+
+        assert_eq!(pointer, path.as_os_str().as_encoded_bytes().as_ptr(), "{}", p);
+        assert_eq!(capacity, path.capacity(), "{}", p);
+    }
+}
+
+#[test]
+fn same_as_parse_dot() {
+    // every path of up to 7 characters
+    let alphabet = ["a", ".", "/"];
+
+    let mut paths = vec![String::new()];
+
+    for _ in 0..7 {
+        paths = paths
+            .iter()
+            .flat_map(|p| alphabet.iter().map(move |c| format!("{}{}", p, c)))
+            .collect();
+
+        for p in paths.iter() {
+            for cwd in ["/cwd/x", "/", "x/y"] {
+                let expected = PathBuf::from(p).parse_dot_from(cwd).unwrap().into_owned();
+
+                let mut path = PathBuf::from(p);
+
+                let changed = path.dedot_in_place_from(cwd).unwrap();
+
+                assert_eq!(expected, path, "{} {}", p, cwd);
+                assert_eq!(expected.as_os_str() != p.as_str(), changed, "{} {}", p, cwd);
+            }
+        }
+    }
+}
+
+#[test]
+fn dedot_in_place_with_cwd() {
+    let cwd = env::current_dir().unwrap();
+
+    let mut path = PathBuf::from("./path/../123");
+
+    assert!(path.dedot_in_place().unwrap());
+    assert_eq!(cwd.join("123"), path);
+}
+
+#[test]
+fn needs_cwd() {
+    for p in ["./path", "../path", ".", ".."] {
+        assert!(Path::new(p).needs_cwd(), "{}", p);
+    }
+
+    for p in ["/path/../123", "path/../../123", "", "/", ".abc", "~/path"] {
+        assert!(!Path::new(p).needs_cwd(), "{}", p);
+        assert!(!Path::new(p).to_path_buf().needs_cwd(), "{}", p);
+    }
+}
+
+#[test]
+fn lazy_cwd() {
+    let mut dedotter = Dedotter::new();
+
+    for p in ["/path/to/../123", "path/../../123", "/"] {
+        assert_eq!(Path::new(p).parse_dot_from("/cwd").unwrap(), dedotter.parse_dot(p).unwrap());
+    }
+
+    assert!(!dedotter.has_cwd());
+}
+
+#[test]
+fn given_cwd() {
+    let mut dedotter = Dedotter::new().cwd("/cwd/sub");
+
+    assert!(dedotter.has_cwd());
+
+    let paths = ["./path", "../path", "/path/../123"];
+
+    let results: Vec<_> = dedotter.parse_dot_all(paths).map(Result::unwrap).collect();
+
+    assert_eq!(
+        vec![Path::new("/cwd/sub/path"), Path::new("/cwd/path"), Path::new("/123")],
+        results
+    );
+}
+
+#[test]
+fn dedotter_reuse() {
+    let mut dedotter = Dedotter::new().cwd("/cwd");
+
+    // the results do not borrow the dedotter
+    let results: Vec<_> = dedotter.parse_dot_all(["./a", "/b/../c"]).map(Result::unwrap).collect();
+
+    assert_eq!(Path::new("/cwd/d"), dedotter.parse_dot("./d").unwrap());
+    assert_eq!(vec![Path::new("/cwd/a"), Path::new("/c")], results);
+}
+
+#[test]
+fn dedotter_options() {
+    let mut dedotter =
+        Dedotter::new().options(DedotOptions::new().escape_policy(EscapePolicy::Error)).cwd("/");
+
+    let results: Vec<_> = dedotter.parse_dot_all(["/a/../b", "/a/../../b", "../b"]).collect();
+
+    assert_eq!(Path::new("/b"), results[0].as_ref().unwrap());
+    assert!(results[1].is_err());
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn fetch_cwd() {
+    let cwd = env::current_dir().unwrap();
+
+    let mut dedotter = Dedotter::new();
+
+    assert_eq!(Path::new("/123"), dedotter.parse_dot("/path/../123").unwrap());
+    assert!(!dedotter.has_cwd());
+
+    assert_eq!(cwd.join("123"), dedotter.parse_dot("./path/../123").unwrap());
+    assert!(dedotter.has_cwd());
+}
+
+fn var_source() -> HashMap<String, OsString> {
+    let mut source = HashMap::new();
+
+    source.insert("DATA_DIR".to_string(), OsString::from("/srv/data"));
+    source.insert("UP".to_string(), OsString::from("../.."));
+    source.insert("EMPTY".to_string(), OsString::new());
+
+    source
+}
+
+fn expand_var(syntax: VarSyntax, p: &str) -> Result<String, VarError> {
+    VarExpander::with_source(syntax, var_source())
+        .expand(Path::new(p))
+        .map(|p| p.to_str().unwrap().to_string())
+}
+
+#[test]
+fn var_posix() {
+    assert_eq!("/srv/data/../logs", expand_var(VarSyntax::Posix, "$DATA_DIR/../logs").unwrap());
+    assert_eq!("/srv/data/logs", expand_var(VarSyntax::Posix, "${DATA_DIR}/logs").unwrap());
+    assert_eq!("/tmp/logs", expand_var(VarSyntax::Posix, "${MISSING:-/tmp}/logs").unwrap());
+    assert_eq!("/tmp/logs", expand_var(VarSyntax::Posix, "${EMPTY:-/tmp}/logs").unwrap());
+    assert_eq!("/logs", expand_var(VarSyntax::Posix, "${EMPTY-/tmp}/logs").unwrap());
+    assert_eq!(
+        "/srv/data/logs",
+        expand_var(VarSyntax::Posix, "${MISSING:-${DATA_DIR}}/logs").unwrap()
+    );
+    assert_eq!("a$/b$", expand_var(VarSyntax::Posix, "a$/b$").unwrap());
+    assert_eq!("%DATA_DIR%", expand_var(VarSyntax::Posix, "%DATA_DIR%").unwrap());
+
+    assert_eq!(
+        Err(VarError::Undefined("MISSING".to_string())),
+        expand_var(VarSyntax::Posix, "$MISSING/a")
+    );
+    assert_eq!(Err(VarError::Invalid(2)), expand_var(VarSyntax::Posix, "a/${DATA_DIR"));
+    assert_eq!(Err(VarError::Invalid(0)), expand_var(VarSyntax::Posix, "${DATA_DIR:x}"));
+}
+
+#[test]
+fn var_windows() {
+    assert_eq!("/srv/data/../logs", expand_var(VarSyntax::Windows, "%DATA_DIR%/../logs").unwrap());
+    assert_eq!("100%/a", expand_var(VarSyntax::Windows, "100%/a").unwrap());
+    assert_eq!("%%/a", expand_var(VarSyntax::Windows, "%%/a").unwrap());
+    assert_eq!("$DATA_DIR", expand_var(VarSyntax::Windows, "$DATA_DIR").unwrap());
+
+    assert_eq!(
+        Err(VarError::Undefined("MISSING".to_string())),
+        expand_var(VarSyntax::Windows, "%MISSING%")
+    );
+}
+
+#[test]
+fn var_undefined_empty() {
+    let expander =
+        VarExpander::with_source(VarSyntax::Posix, var_source()).undefined(UndefinedVar::Empty);
+
+    assert_eq!("/a", expander.expand(Path::new("$MISSING/a")).unwrap().to_str().unwrap());
+}
+
+#[test]
+fn var_parse_dot() {
+    let expander = VarExpander::with_source(VarSyntax::Posix, var_source());
+
+    assert_eq!(
+        "/srv/logs",
+        expander
+            .parse_dot_from(Path::new("$DATA_DIR/../logs"), "/", DedotOptions::new())
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+    assert_eq!(
+        "logs",
+        expander
+            .parse_dot_from(Path::new("a/$UP/logs"), "/", DedotOptions::new())
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+    assert_eq!(
+        "/foo/logs",
+        expander
+            .parse_dot_from(Path::new("./${MISSING:-.}/logs"), "/foo", DedotOptions::new())
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+
+    let closure = |name: &str| if name == "X" { Some(OsString::from("x")) } else { None };
+
+    assert_eq!(
+        "a/x",
+        VarExpander::with_source(VarSyntax::Posix, closure)
+            .expand(Path::new("a/$X"))
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+}
+
+fn parse_escape(p: &str, cwd: &str, escape_policy: EscapePolicy) -> io::Result<String> {
+    let options = DedotOptions::new().escape_policy(escape_policy);
+
+    Path::new(p).parse_dot_from_with(cwd, options).map(|p| p.to_str().unwrap().to_string())
+}
+
+fn escape_index(p: &str, cwd: &str) -> usize {
+    let error = parse_escape(p, cwd, EscapePolicy::Error).unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, error.kind());
+
+    let error = DedotError::downcast_ref(&error).unwrap();
+
+    assert_eq!(Path::new(p), error.path());
+
+    error.index()
+}
+
+#[test]
+fn escape_clamp() {
+    assert_eq!(
+        "123/456",
+        parse_escape("path/to/../../../../123/456", "/foo", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(
+        "/123/456",
+        parse_escape("/path/../../123/456", "/foo", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!("/abc", parse_escape("../abc", "/", EscapePolicy::Clamp).unwrap());
+    assert_eq!("abc", parse_escape("../abc", "", EscapePolicy::Clamp).unwrap());
+}
+
+#[test]
+fn escape_error() {
+    assert_eq!("123", parse_escape("path/../123", "/foo", EscapePolicy::Error).unwrap());
+    assert_eq!("/123", parse_escape("../../123", "/foo/bar", EscapePolicy::Error).unwrap());
+
+    assert_eq!(2, escape_index("path/../../123", "/foo"));
+    assert_eq!(3, escape_index("/path/../../123", "/foo"));
+    assert_eq!(0, escape_index("../abc", "/"));
+    assert_eq!(0, escape_index("../abc", ""));
+    assert_eq!(1, escape_index("../../abc", "/foo"));
+}
+
+#[test]
+fn escape_preserve() {
+    assert_eq!(
+        "../../123/456",
+        parse_escape("path/to/../../../../123/456", "/foo", EscapePolicy::Preserve).unwrap()
+    );
+    assert_eq!(
+        "/123/456",
+        parse_escape("/path/../../123/456", "/foo", EscapePolicy::Preserve).unwrap()
+    );
+    assert_eq!("/abc", parse_escape("../abc", "/", EscapePolicy::Preserve).unwrap());
+    assert_eq!("../abc", parse_escape("../abc", "", EscapePolicy::Preserve).unwrap());
+    assert_eq!("../abc", parse_escape("../../abc", "foo", EscapePolicy::Preserve).unwrap());
+}
+
+#[test]
+fn from_file_url() {
+    assert_eq!(
+        Path::new("/path/to/777"),
+        url::from_file_url("file:///path/to/123/../777").unwrap()
+    );
+    assert_eq!(Path::new("/path/to"), url::from_file_url("file:///path/./to/").unwrap());
+    assert_eq!(Path::new("/"), url::from_file_url("file:///../..").unwrap());
+    assert_eq!(Path::new("/path"), url::from_file_url("file://localhost/path").unwrap());
+    assert_eq!(Path::new("/path"), url::from_file_url("FILE:/path?query#fragment").unwrap());
+}
+
+#[test]
+fn from_file_url_percent_decode() {
+    assert_eq!(Path::new("/my docs/777"), url::from_file_url("file:///my%20docs/777").unwrap());
+    assert_eq!(Path::new("/path/777"), url::from_file_url("file:///path/to/%2e%2E/777").unwrap());
+    assert_eq!(Path::new(r"/C:\path"), url::from_file_url("file:///C:%5Cpath").unwrap());
+    assert_eq!(Path::new(OsStr::from_bytes(b"/\xFF")), url::from_file_url("file:///%FF").unwrap());
+}
+
+#[test]
+fn from_file_url_error() {
+    for (u, error) in [
+        ("http://host/path", FileUrlError::NotFileScheme),
+        ("file", FileUrlError::NotFileScheme),
+        ("file://server/share/x", FileUrlError::UnsupportedHost("server".to_string())),
+        ("file:///path/%2", FileUrlError::InvalidPercentEncoding(13)),
+        ("file:///path/%zz", FileUrlError::InvalidPercentEncoding(13)),
+        ("file:///path/a%2Fb", FileUrlError::EncodedSeparator(14)),
+        ("file:path/to", FileUrlError::NotAbsolute),
+        ("file://", FileUrlError::NotAbsolute),
+    ] {
+        assert_eq!(error, url::from_file_url(u).unwrap_err(), "{}", u);
+    }
+}
+
+#[test]
+fn to_file_url() {
+    assert_eq!("file:///path/to/777", url::to_file_url(Path::new("/path/to/123/../777")).unwrap());
+    assert_eq!("file:///", url::to_file_url(Path::new("/path/..")).unwrap());
+    assert_eq!(
+        "file:///my%20docs/100%25/a%23b%3Fc",
+        url::to_file_url(Path::new("/my docs/100%/a#b?c")).unwrap()
+    );
+    assert_eq!("file:///%FF", url::to_file_url(Path::new(OsStr::from_bytes(b"/\xFF"))).unwrap());
+
+    assert_eq!(FileUrlError::NotAbsolute, url::to_file_url(Path::new("path/to")).unwrap_err());
+}
+
+#[test]
+fn file_url_round_trip() {
+    for p in ["/", "/path/to/777", "/my docs/100%/a#b?c", r"/C:\path"] {
+        let url = url::to_file_url(Path::new(p)).unwrap();
+
+        assert_eq!(Path::new(p), url::from_file_url(&url).unwrap(), "{}", url);
+    }
+}
+
+#[test]
+fn is_dedotted() {
+    for p in [
+        "/path/to/123",
+        "path/to/123",
+        "/",
+        "",
+        ".abc",
+        "/path//123",
+        "/path/",
+        "./path",
+        "..",
+        "/a/../b",
+    ] {
+        let p = Path::new(p);
+
+        let borrowed = matches!(p.parse_dot_from("/cwd").unwrap(), Cow::Borrowed(_));
+
+        assert_eq!(borrowed, p.is_dedotted(), "{:?}", p);
+        assert_eq!(borrowed, p.to_path_buf().is_dedotted(), "{:?}", p);
+    }
+}
+
+fn prepare(name: &str) -> PathBuf {
+    let root = fs::canonicalize(env::temp_dir()).unwrap().join(format!(
+        "path-dedot-{}-{}",
+        name,
+        std::process::id()
+    ));
+
+    let _ = fs::remove_dir_all(&root);
+
+    fs::create_dir_all(root.join("real/dir")).unwrap();
+    fs::create_dir_all(root.join("other")).unwrap();
+
+    symlink(root.join("real/dir"), root.join("other/link")).unwrap();
+
+    root
+}
+
+#[test]
+fn physical_symlink() {
+    let root = prepare("physical_symlink");
+
+    let p = root.join("other/link/../new/./file");
+
+    assert_eq!(root.join("real/new/file"), p.parse_dot_physical().unwrap());
+
+    // lexically, it is different
+    assert_eq!(root.join("other/new/file"), p.parse_dot().unwrap());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn physical_missing() {
+    let root = prepare("physical_missing");
+
+    assert_eq!(
+        root.join("real/a/c"),
+        root.join("other/link/../a/b/../c").parse_dot_physical().unwrap()
+    );
+    assert_eq!(
+        root.join("real"),
+        root.join("missing/../other/link/..").parse_dot_physical().unwrap()
+    );
+    assert_eq!(
+        root.join("real/dir/x"),
+        PathBuf::from("./link/x").parse_dot_physical_from(root.join("other")).unwrap()
+    );
+    assert_eq!(
+        root.join("real"),
+        PathBuf::from("../link/..").parse_dot_physical_from(root.join("other/x")).unwrap()
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn physical_not_a_directory() {
+    let root = prepare("physical_not_a_directory");
+
+    fs::write(root.join("real/file"), b"").unwrap();
+
+    let error = root.join("real/file/../dir").parse_dot_physical().unwrap_err();
+
+    assert_eq!(Some(libc::ENOTDIR), error.raw_os_error());
+
+    // a missing component is still removed lexically
+    assert_eq!(
+        root.join("real/dir"),
+        root.join("real/missing/../dir").parse_dot_physical().unwrap()
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn physical_relative_cwd() {
+    let error = Path::new("../x").parse_dot_physical_from("relative/cwd").unwrap_err();
+
+    assert!(matches!(DedotError::downcast_ref(&error), Some(DedotError::RelativeCwd { .. })));
+
+    let error = Path::new("..").parse_dot_physical_from("").unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, error.kind());
+}
+
+#[test]
+fn physical_root() {
+    assert_eq!(PathBuf::from("/"), PathBuf::from("/../..").parse_dot_physical().unwrap());
+    assert_eq!(
+        PathBuf::from("/path-dedot-missing/a"),
+        PathBuf::from("/path-dedot-missing/b/../a").parse_dot_physical().unwrap()
+    );
+}
+
+fn relative(target: &str, base: &str, cwd: &str) -> String {
+    Path::new(target).relative_to(base, cwd).unwrap().to_str().unwrap().to_string()
+}
+
+#[test]
+fn relative_to_lv0() {
+    assert_eq!(".", relative("/a/b", "/a/b", "/"));
+    assert_eq!(".", relative("/a/./b", "/a/c/../b/", "/"));
+    assert_eq!("c/d", relative("/a/b/c/d", "/a/b", "/"));
+    assert_eq!("../..", relative("/a/b", "/a/b/c/d", "/"));
+    assert_eq!("../../x/y", relative("/a/b/x/y", "/a/b/c/d", "/"));
+    assert_eq!("../a/b", relative("/a/b", "/c", "/"));
+}
+
+#[test]
+fn relative_to_lv1() {
+    assert_eq!("../x", relative("./x", "./y", "/foo"));
+    assert_eq!("../foo/x", relative("./x", "../y", "/foo"));
+    assert_eq!("x", relative("/foo/x", ".", "/foo"));
+    assert_eq!("b", relative("a/b", "a", "/foo"));
+    assert_eq!("../b", relative("b", "a", "/foo"));
+}
+
+#[test]
+fn relative_to_error() {
+    assert!(Path::new("/a").relative_to("b", "/").is_err());
+    assert!(Path::new("a").relative_to("/b", "/").is_err());
+
+    let error = Path::new("/a").relative_to("b", "/").unwrap_err();
+
+    match DedotError::downcast_ref(&error) {
+        Some(DedotError::PrefixMismatch {
+            path,
+            base,
+            index,
+        }) => {
+            assert_eq!(Path::new("/a"), path);
+            assert_eq!(Path::new("b"), base);
+            assert_eq!(0, *index);
+        },
+        _ => panic!("{:?}", error),
+    }
+    assert!(Path::new("a").relative_to("../b", "").is_ok());
+}
+
+#[test]
+fn symlink_aware_keep() {
+    let root = prepare("symlink_aware_keep");
+
+    let p = root.join("other/link/a/../../b");
+
+    let result = p.parse_dot_from_symlink_aware("/", SymlinkPolicy::Keep).unwrap();
+
+    assert_eq!(root.join("other/link/../b"), result.path);
+    assert_eq!(
+        vec![(root.join("other/link/a"), false), (root.join("other/link"), true)],
+        result.checked.iter().map(|c| (c.path.clone(), c.is_symlink)).collect::<Vec<_>>()
+    );
+    let depth = root.components().count();
+
+    assert_eq!(
+        vec![depth + 3, depth + 4],
+        result.checked.iter().map(|c| c.index).collect::<Vec<_>>()
+    );
+
+    let result = PathBuf::from("./link/../../x")
+        .parse_dot_from_symlink_aware(root.join("other"), SymlinkPolicy::Keep)
+        .unwrap();
+
+    assert_eq!(root.join("other/link/../../x"), result.path);
+    assert_eq!(1, result.checked.len());
+
+    let result = PathBuf::from("other/a/../x")
+        .parse_dot_from_symlink_aware(&root, SymlinkPolicy::Keep)
+        .unwrap();
+
+    assert_eq!(PathBuf::from("other/x"), result.path);
+    assert_eq!(PathBuf::from("other/a"), result.checked[0].path);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn symlink_aware_error() {
+    let root = prepare("symlink_aware_error");
+
+    let p = root.join("other/link/..");
+
+    let error = p.parse_dot_from_symlink_aware("/", SymlinkPolicy::Error).unwrap_err();
+
+    let error = match DedotError::downcast_ref(&error) {
+        Some(DedotError::Symlink(error)) => error,
+        _ => panic!("{:?}", error),
+    };
+
+    assert_eq!(root.join("other/link"), error.symlink());
+    assert_eq!(p, error.path());
+
+    let result =
+        root.join("real/dir/..").parse_dot_from_symlink_aware("/", SymlinkPolicy::Error).unwrap();
+
+    assert_eq!(root.join("real"), result.path);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn symlink_aware_lexical() {
+    let result = PathBuf::from("/path-dedot-missing/to/../../../123/./456")
+        .parse_dot_from_symlink_aware("/", SymlinkPolicy::Error)
+        .unwrap();
+
+    assert_eq!(PathBuf::from("/123/456"), result.path);
+    assert_eq!(2, result.checked.len());
+}
+
+#[test]
+fn tilde() {
+    env::set_var("HOME", "/home/path-dedot");
+
+    let options = DedotOptions::new().expand_tilde(true);
+
+    assert_eq!("/home/path-dedot", expand_tilde(Path::new("~")).unwrap().to_str().unwrap());
+    assert_eq!("/home/path-dedot/a", expand_tilde(Path::new("~/a")).unwrap().to_str().unwrap());
+    assert_eq!(
+        "/home/b",
+        Path::new("~/../b").parse_dot_from_with("/", options).unwrap().to_str().unwrap()
+    );
+    assert_eq!("b", Path::new("~/../b").parse_dot_from("/").unwrap().to_str().unwrap());
+
+    assert_eq!(
+        "a/~/b",
+        Path::new("a/~/b").parse_dot_from_with("/", options).unwrap().to_str().unwrap()
+    );
+    assert_eq!(
+        "/foo/~",
+        Path::new("./~").parse_dot_from_with("/foo", options).unwrap().to_str().unwrap()
+    );
+}
+
+#[test]
+fn tilde_user() {
+    assert_eq!(
+        "/root/a",
+        expand_tilde(Path::new("~root/./a"))
+            .unwrap()
+            .parse_dot_from("/")
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+
+    assert_eq!(
+        TildeError::NoSuchUser(OsString::from("path-dedot-no-such-user")),
+        expand_tilde(Path::new("~path-dedot-no-such-user/a")).unwrap_err()
+    );
+
+    // a user name which is not UTF-8 is still looked up
+    assert_eq!(
+        TildeError::NoSuchUser(OsString::from_vec(b"path-dedot-\xFF".to_vec())),
+        expand_tilde(Path::new(OsStr::from_bytes(b"~path-dedot-\xFF/a"))).unwrap_err()
+    );
+
+    let error = Path::new("~path-dedot-no-such-user/a")
+        .parse_dot_from_with("/", DedotOptions::new().expand_tilde(true))
+        .unwrap_err();
+
+    assert!(matches!(DedotError::downcast_ref(&error), Some(DedotError::Tilde { .. })));
+}
+
+fn parse_trailing(p: &str, cwd: &str) -> Cow<'static, str> {
+    let options = DedotOptions::new().keep_trailing_separator(true);
+
+    let p: &'static Path = Box::leak(Path::new(p).to_path_buf().into_boxed_path());
+
+    match p.parse_dot_from_with(cwd, options).unwrap() {
+        Cow::Borrowed(p) => Cow::Borrowed(p.to_str().unwrap()),
+        Cow::Owned(p) => Cow::Owned(p.into_os_string().into_string().unwrap()),
+    }
+}
+
+#[test]
+fn trailing_separator() {
+    assert_eq!("dir/", parse_trailing("dir/", "/foo"));
+    assert_eq!("dir/", parse_trailing("dir/sub/..", "/foo"));
+    assert_eq!("dir/sub/", parse_trailing("dir/sub/.", "/foo"));
+    assert_eq!("dir/sub/", parse_trailing("dir//sub//", "/foo"));
+    assert_eq!("/foo/", parse_trailing("./", "/foo"));
+    assert_eq!("/foo/", parse_trailing(".", "/foo"));
+    assert_eq!("/", parse_trailing("..", "/foo"));
+    assert_eq!("/", parse_trailing("/dir/..", "/foo"));
+    assert_eq!("", parse_trailing("dir/..", "/foo"));
+    assert_eq!("dir/file", parse_trailing("dir/./file", "/foo"));
+}
+
+#[test]
+fn trailing_separator_borrowed() {
+    assert!(matches!(parse_trailing("dir/", "/foo"), Cow::Borrowed(_)));
+    assert!(matches!(parse_trailing("/dir/sub/", "/foo"), Cow::Borrowed(_)));
+    assert!(matches!(parse_trailing("/", "/foo"), Cow::Borrowed(_)));
+    assert!(matches!(parse_trailing("dir/sub/.", "/foo"), Cow::Owned(_)));
+}
@@ -0,0 +1,59 @@
+#![cfg(unix)]
+
+use std::{env, fs, path::Path};
+
+use path_dedot::{
+    cwd_strategy, set_cwd_strategy, update_cwd, CachedCwd, CwdPath, CwdProvider, CwdStrategy,
+    ParseDot,
+};
+
+#[test]
+fn switch_strategy() {
+    let cache_by_default = cfg!(any(
+        feature = "once_cell_cache",
+        feature = "lazy_static_cache",
+        feature = "unsafe_cache"
+    ));
+
+    assert_eq!(
+        if cache_by_default { CwdStrategy::Cache } else { CwdStrategy::Fetch },
+        cwd_strategy()
+    );
+
+    let root = fs::canonicalize(env::temp_dir()).unwrap();
+
+    let p = Path::new("./path/to/123/456");
+
+    set_cwd_strategy(CwdStrategy::Cache);
+    assert_eq!(CwdStrategy::Cache, cwd_strategy());
+
+    env::set_current_dir("/").unwrap();
+    update_cwd().unwrap();
+
+    assert_eq!(Path::new("/path/to/123/456"), p.parse_dot().unwrap());
+
+    // the cached CWD is kept until it is updated
+    env::set_current_dir(&root).unwrap();
+
+    assert_eq!(Path::new("/path/to/123/456"), p.parse_dot().unwrap());
+
+    let previous = CachedCwd.cwd().unwrap();
+
+    assert!(matches!(previous, CwdPath::Shared(_)));
+
+    update_cwd().unwrap();
+
+    assert_eq!(root.join("path/to/123/456"), p.parse_dot().unwrap());
+
+    // a reader which still holds the previous CWD is not affected
+    assert_eq!(Path::new("/"), &*previous);
+    assert_eq!(root, &*CachedCwd.cwd().unwrap());
+
+    // the CWD is fetched every time
+    set_cwd_strategy(CwdStrategy::Fetch);
+    assert_eq!(CwdStrategy::Fetch, cwd_strategy());
+
+    env::set_current_dir("/").unwrap();
+
+    assert_eq!(Path::new("/path/to/123/456"), p.parse_dot().unwrap());
+}
@@ -0,0 +1,57 @@
+#![cfg(unix)]
+
+use std::{env, fs, io::ErrorKind, path::Path};
+
+use path_dedot::{
+    set_cwd_strategy, CwdStrategy, DedotError, ParseDot, ParseDotFs, ParseDotMut, SymlinkPolicy,
+};
+
+#[test]
+fn deleted_cwd() {
+    set_cwd_strategy(CwdStrategy::Fetch);
+
+    let root = fs::canonicalize(env::temp_dir())
+        .unwrap()
+        .join(format!("path-dedot-deleted-cwd-{}", std::process::id()));
+
+    fs::create_dir_all(&root).unwrap();
+    env::set_current_dir(&root).unwrap();
+    fs::remove_dir(&root).unwrap();
+
+    // the CWD is only needed for a leading Single Dot or Double Dots
+    assert_eq!(Path::new("/path/123"), Path::new("/path/to/../123").parse_dot().unwrap());
+    assert_eq!(Path::new("path/123"), Path::new("path/to/../123").parse_dot().unwrap());
+
+    let mut path = Path::new("/path/to/../123").to_path_buf();
+
+    assert!(path.dedot_in_place().unwrap());
+    assert_eq!(Path::new("/path/123"), path);
+
+    assert_eq!(Path::new("/"), Path::new("/path-dedot-missing/..").parse_dot_physical().unwrap());
+    assert_eq!(
+        Path::new("/"),
+        Path::new("/path-dedot-missing/..")
+            .parse_dot_symlink_aware(SymlinkPolicy::Error)
+            .unwrap()
+            .path
+    );
+    assert_eq!(
+        Path::new("path"),
+        Path::new("path/to/..").parse_dot_symlink_aware(SymlinkPolicy::Error).unwrap().path
+    );
+
+    for p in ["./path", "../path"] {
+        let error = Path::new(p).parse_dot().unwrap_err();
+
+        assert_eq!(ErrorKind::NotFound, error.kind());
+
+        let error = DedotError::downcast_ref(&error).unwrap();
+
+        assert!(matches!(error, DedotError::CwdUnavailable { .. }));
+        assert_eq!(Path::new(p), error.path());
+        assert_eq!(0, error.index());
+
+        assert!(Path::new(p).parse_dot_physical().is_err());
+        assert!(Path::new(p).parse_dot_symlink_aware(SymlinkPolicy::Error).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(unix)]
+
+use std::{env, fs, path::Path};
+
+use path_dedot::{init_cwd, is_cwd_cached, set_cwd_strategy, CwdStrategy, DedotError, ParseDot};
+
+#[test]
+fn init_cwd_without_panicking() {
+    set_cwd_strategy(CwdStrategy::Cache);
+
+    assert!(!is_cwd_cached());
+
+    let root = fs::canonicalize(env::temp_dir())
+        .unwrap()
+        .join(format!("path-dedot-init-cwd-{}", std::process::id()));
+
+    fs::create_dir_all(&root).unwrap();
+    env::set_current_dir(&root).unwrap();
+    fs::remove_dir(&root).unwrap();
+
+    // the deleted CWD cannot be cached
+    assert!(init_cwd().is_err());
+    assert!(!is_cwd_cached());
+
+    let error = Path::new("./path").parse_dot().unwrap_err();
+
+    assert!(matches!(DedotError::downcast_ref(&error).unwrap(), DedotError::CwdUnavailable { .. }));
+    assert!(!is_cwd_cached());
+
+    env::set_current_dir("/").unwrap();
+
+    init_cwd().unwrap();
+    assert!(is_cwd_cached());
+    assert_eq!(Path::new("/path"), Path::new("./path").parse_dot().unwrap());
+
+    // the cached CWD is not replaced by `init_cwd`
+    env::set_current_dir(env::temp_dir()).unwrap();
+
+    init_cwd().unwrap();
+    assert_eq!(Path::new("/path"), Path::new("./path").parse_dot().unwrap());
+}
@@ -6,9 +6,7 @@ use path_dedot::{update_cwd, ParseDot};
 
 #[test]
 fn dedot_after_updating_cwd() {
-    unsafe {
-        update_cwd();
-    }
+    update_cwd().unwrap();
 
     let p = Path::new("./path/to/123/456");
 
@@ -21,9 +19,7 @@ fn dedot_after_updating_cwd() {
 
     env::set_current_dir("/").unwrap();
 
-    unsafe {
-        update_cwd();
-    }
+    update_cwd().unwrap();
 
     assert_eq!(
         Path::join(env::current_dir().unwrap().as_path(), Path::new("path/to/123/456"))
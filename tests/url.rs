@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+
+use path_dedot::url::remove_dot_segments;
+
+#[test]
+fn rfc3986_remove_dot_segments() {
+    // RFC 3986 §5.2.4
+    assert_eq!("/a/g", remove_dot_segments("/a/b/c/./../../g"));
+    assert_eq!("mid/6", remove_dot_segments("mid/content=5/../6"));
+}
+
+#[test]
+fn rfc3986_normal_examples() {
+    // RFC 3986 §5.4.1, the paths merged with the base `http://a/b/c/d;p?q`
+    assert_eq!("/b/c/g", remove_dot_segments("/b/c/./g"));
+    assert_eq!("/b/c/g/", remove_dot_segments("/b/c/g/"));
+    assert_eq!("/b/c/", remove_dot_segments("/b/c/."));
+    assert_eq!("/b/c/", remove_dot_segments("/b/c/./"));
+    assert_eq!("/b/", remove_dot_segments("/b/c/.."));
+    assert_eq!("/b/", remove_dot_segments("/b/c/../"));
+    assert_eq!("/b/g", remove_dot_segments("/b/c/../g"));
+    assert_eq!("/", remove_dot_segments("/b/c/../.."));
+    assert_eq!("/", remove_dot_segments("/b/c/../../"));
+    assert_eq!("/g", remove_dot_segments("/b/c/../../g"));
+}
+
+#[test]
+fn rfc3986_abnormal_examples() {
+    // RFC 3986 §5.4.2, the paths merged with the base `http://a/b/c/d;p?q`
+    assert_eq!("/g", remove_dot_segments("/b/c/../../../g"));
+    assert_eq!("/g", remove_dot_segments("/b/c/../../../../g"));
+    assert_eq!("/g", remove_dot_segments("/./g"));
+    assert_eq!("/g", remove_dot_segments("/../g"));
+    assert_eq!("/b/c/g.", remove_dot_segments("/b/c/g."));
+    assert_eq!("/b/c/.g", remove_dot_segments("/b/c/.g"));
+    assert_eq!("/b/c/g..", remove_dot_segments("/b/c/g.."));
+    assert_eq!("/b/c/..g", remove_dot_segments("/b/c/..g"));
+    assert_eq!("/b/g", remove_dot_segments("/b/c/./../g"));
+    assert_eq!("/b/c/g/", remove_dot_segments("/b/c/./g/."));
+    assert_eq!("/b/c/g/h", remove_dot_segments("/b/c/g/./h"));
+    assert_eq!("/b/c/h", remove_dot_segments("/b/c/g/../h"));
+    assert_eq!("/b/c/g;x=1/y", remove_dot_segments("/b/c/g;x=1/./y"));
+    assert_eq!("/b/c/y", remove_dot_segments("/b/c/g;x=1/../y"));
+}
+
+#[test]
+fn relative() {
+    assert_eq!("", remove_dot_segments("."));
+    assert_eq!("", remove_dot_segments(".."));
+    assert_eq!("", remove_dot_segments("../"));
+    assert_eq!("g", remove_dot_segments("../../g"));
+    assert_eq!("/b", remove_dot_segments("a/../b"));
+}
+
+#[test]
+fn percent_encoded_dots() {
+    assert_eq!("/a/g", remove_dot_segments("/a/b/c/%2e/%2E%2e/.%2e/g"));
+    assert_eq!("/a/", remove_dot_segments("/a/b/%2e."));
+    assert_eq!("/a/b/%2e%2e%2e", remove_dot_segments("/a/b/%2e%2e%2e"));
+    assert_eq!("/a/b/%2f..", remove_dot_segments("/a/b/%2f.."));
+}
+
+#[test]
+fn params_query_and_fragment() {
+    assert_eq!("/a/..;x/b", remove_dot_segments("/a/..;x/b"));
+    assert_eq!("/a/?x=/../y", remove_dot_segments("/a/b/..?x=/../y"));
+    assert_eq!("/a/#/../y", remove_dot_segments("/a/./#/../y"));
+    assert_eq!("?../", remove_dot_segments("?../"));
+}
+
+#[test]
+fn borrowed() {
+    for path in ["", "/", "/a/b/c", "a/b;x/c?d=../e", "/a/.b/..c/%2e%2e%2e", "/日本/語"] {
+        assert!(matches!(remove_dot_segments(path), Cow::Borrowed(_)), "{}", path);
+    }
+
+    assert!(matches!(remove_dot_segments("/a/.."), Cow::Owned(_)));
+}
@@ -1,8 +1,23 @@
-#![cfg(all(windows, not(feature = "unsafe_cache")))]
-
-use std::{env, path::Path};
-
-use path_dedot::{ParseDot, ParsePrefix};
+#![cfg(windows)]
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env,
+    ffi::{OsStr, OsString},
+    fs,
+    io::{self, ErrorKind},
+    os::windows::ffi::OsStringExt,
+    path::{Component, Path, PathBuf},
+};
+
+use path_dedot::{
+    expand_tilde, join_confined,
+    url::{self, FileUrlError},
+    CachedCwd, Clean, CwdPath, CwdProvider, DedotError, DedotOptions, Dedotter, DefaultCwd,
+    EscapePolicy, ParseDot, ParseDotFs, ParseDotMut, ParsePrefix, ProcessCwd, SymlinkPolicy,
+    TildeError, VarExpander, VarSyntax,
+};
 
 #[test]
 fn dedot_lv0_1() {
@@ -27,7 +42,7 @@ fn dedot_lv0_2() {
     match cwd_parent {
         Some(cwd_parent) => {
             assert_eq!(
-                Path::join(&cwd_parent, Path::new(r"path\to\123\456")).to_str().unwrap(),
+                Path::join(cwd_parent, Path::new(r"path\to\123\456")).to_str().unwrap(),
                 p.parse_dot().unwrap().to_str().unwrap()
             );
         },
@@ -72,7 +87,7 @@ fn dedot_lv0_4() {
     match cwd_parent {
         Some(cwd_parent) => {
             assert_eq!(
-                Path::join(&cwd_parent, Path::new(r"path\to\123\456")).to_str().unwrap(),
+                Path::join(cwd_parent, Path::new(r"path\to\123\456")).to_str().unwrap(),
                 p.parse_dot().unwrap().to_str().unwrap()
             );
         },
@@ -341,3 +356,769 @@ fn prefix_4() {
 
     assert_eq!(r"\\VBOXSRV\test\", p.parse_dot().unwrap().to_str().unwrap());
 }
+
+#[test]
+fn clean_lv0() {
+    assert_eq!(".", Path::new("").clean().to_str().unwrap());
+    assert_eq!(".", Path::new(".").clean().to_str().unwrap());
+    assert_eq!(r"\", Path::new(r"\").clean().to_str().unwrap());
+    assert_eq!("..", Path::new("..").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_lv1() {
+    assert_eq!(r"path\to\123\456", Path::new(r".\path\to\123\456").clean().to_str().unwrap());
+    assert_eq!(r"..\path\to\123\456", Path::new(r"..\path\to\123\456").clean().to_str().unwrap());
+}
+
+#[test]
+fn clean_lv2() {
+    assert_eq!(
+        r"C:\path\123\456",
+        Path::new(r"C:\path\to\..\123\456\.\777\..").clean().to_str().unwrap()
+    );
+    assert_eq!(
+        r"path\123\456",
+        Path::new(r"path\to\..\123\456\.\777\..").clean().to_str().unwrap()
+    );
+}
+
+#[test]
+fn clean_lv3() {
+    assert_eq!(
+        r"..\..\123\456",
+        Path::new(r"path\to\..\..\..\..\123\456\.\777\..").clean().to_str().unwrap()
+    );
+    assert_eq!(
+        r"C:\123\456",
+        Path::new(r"C:\path\to\..\..\..\..\123\456\.\777\..").clean().to_str().unwrap()
+    );
+    assert_eq!(
+        r"\\server\share\123",
+        Path::new(r"\\server\share\path\..\..\123").clean().to_str().unwrap()
+    );
+}
+
+#[test]
+fn clean_lv4() {
+    assert_eq!(r"C:..\path", Path::new(r"C:..\path").clean().to_str().unwrap());
+    assert_eq!(r"C:path", Path::new(r"C:.\path").clean().to_str().unwrap());
+    assert_eq!(".", Path::new(r"path\..").clean().to_str().unwrap());
+    assert_eq!(r"C:\", Path::new(r"C:\..").clean().to_str().unwrap());
+}
+
+fn confine(root: &str, untrusted: &str, escape_policy: EscapePolicy) -> io::Result<String> {
+    join_confined(root, untrusted, escape_policy).map(|p| p.to_str().unwrap().to_string())
+}
+
+fn confine_escape_index(root: &str, untrusted: &str) -> usize {
+    let error = confine(root, untrusted, EscapePolicy::Error).unwrap_err();
+
+    let error = DedotError::downcast_ref(&error).unwrap();
+
+    assert_eq!(Path::new(untrusted), error.path());
+
+    error.index()
+}
+
+#[test]
+fn confine_clamp() {
+    assert_eq!(r"C:\upload\a\b", confine(r"C:\upload", r"a\.\b", EscapePolicy::Clamp).unwrap());
+    assert_eq!(r"C:\upload\b", confine(r"C:\upload", r"..\..\b", EscapePolicy::Clamp).unwrap());
+    assert_eq!(
+        r"C:\upload\Windows",
+        confine(r"C:\upload", r"D:\Windows", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(
+        r"C:\upload\Windows",
+        confine(r"C:\upload", r"D:Windows", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(
+        r"C:\upload\x",
+        confine(r"C:\upload", r"\\server\share\x", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(r"C:\upload\x", confine(r"C:\upload", r"a\..\C:x", EscapePolicy::Clamp).unwrap());
+    assert_eq!(
+        r"C:\upload\x",
+        confine(r"C:\upload", r"\\?\C:\a/../../x", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(r"C:\upload\x", confine(r"C:\upload", r".. \x", EscapePolicy::Clamp).unwrap());
+    assert_eq!(r"C:\upload\x", confine(r"C:\upload", r"a\.. \x", EscapePolicy::Clamp).unwrap());
+}
+
+#[test]
+fn confine_error() {
+    assert_eq!(0, confine_escape_index(r"C:\upload", r"..\b"));
+    assert_eq!(0, confine_escape_index(r"C:\upload", r"D:\Windows"));
+    assert_eq!(0, confine_escape_index(r"C:\upload", r"\Windows"));
+    assert_eq!(0, confine_escape_index(r"C:\upload", r".. \x"));
+    assert_eq!(0, confine_escape_index(r"C:\upload", r"\\?\C:\a/../../x"));
+}
+
+struct NoCwd;
+
+impl CwdProvider for NoCwd {
+    fn cwd(&self) -> io::Result<CwdPath<'_>> {
+        Err(io::Error::new(ErrorKind::NotFound, "no CWD"))
+    }
+}
+
+#[test]
+fn fixed_path() {
+    let options = DedotOptions::new();
+
+    assert_eq!(
+        Path::new(r"C:\foo\bar\path"),
+        Path::new(r".\path").parse_dot_with_provider(Path::new(r"C:\foo\bar"), options).unwrap()
+    );
+    assert_eq!(
+        Path::new(r"C:\foo\path"),
+        Path::new(r"..\path")
+            .parse_dot_with_provider(&PathBuf::from(r"C:\foo\bar"), options)
+            .unwrap()
+    );
+
+    let mut path = PathBuf::from(r"..\path\..\123");
+
+    assert!(path.dedot_in_place_with_provider(Path::new(r"\\server\share\foo")).unwrap());
+    assert_eq!(Path::new(r"\\server\share\123"), path);
+}
+
+#[test]
+fn provider_error() {
+    assert_eq!(
+        Path::new(r"C:\path\123"),
+        Path::new(r"C:\path\to\..\123")
+            .parse_dot_with_provider(&NoCwd, DedotOptions::new())
+            .unwrap()
+    );
+
+    for (p, index) in [(r".\path", 0), (r"C:..\path", 1)] {
+        let error = Path::new(p).parse_dot_with_provider(&NoCwd, DedotOptions::new()).unwrap_err();
+
+        assert_eq!(ErrorKind::NotFound, error.kind());
+
+        let error = DedotError::downcast_ref(&error).unwrap();
+
+        assert!(matches!(error, DedotError::CwdUnavailable { .. }));
+        assert_eq!(Path::new(p), error.path());
+        assert_eq!(index, error.index());
+    }
+}
+
+#[test]
+fn process_providers() {
+    let cwd = env::current_dir().unwrap();
+
+    let p = Path::new(r".\path\to\123");
+
+    for provider in [&DefaultCwd as &dyn CwdProvider, &ProcessCwd, &CachedCwd] {
+        assert_eq!(
+            cwd.join(r"path\to\123"),
+            p.parse_dot_with_provider(provider, DedotOptions::new()).unwrap()
+        );
+    }
+}
+
+#[test]
+fn dedot_components() {
+    let mut iter = Path::new(r".\path\to\..\123").dedot_components(Path::new(r"C:\cwd")).unwrap();
+
+    assert!(matches!(iter.next(), Some(Component::Prefix(prefix)) if prefix.as_os_str() == "C:"));
+    assert_eq!(
+        vec![
+            Component::RootDir,
+            Component::Normal(OsStr::new("cwd")),
+            Component::Normal(OsStr::new("path")),
+            Component::Normal(OsStr::new("123")),
+        ],
+        iter.collect::<Vec<_>>()
+    );
+
+    assert_eq!(0, Path::new("").dedot_components(Path::new(r"C:\cwd")).unwrap().count());
+}
+
+#[test]
+fn same_as_parse_dot_from() {
+    for cwd in [r"C:\", r"C:\foo\bar", r"\\server\share\foo", r"\\?\C:\foo", r"\foo", "foo"] {
+        for p in [
+            r"C:\path\to\123\..\777",
+            r"C:path\..\..\777",
+            r"\\server\share\path\..\..\777",
+            r"\\?\C:\path\..\777",
+            r"\path\..\777",
+            r".\abc",
+            r"..\abc",
+            r"..\..\abc\..",
+            r"C:.\abc",
+            r"C:..\abc",
+            ".",
+            "..",
+        ] {
+            let p = Path::new(p);
+            let cwd = Path::new(cwd);
+
+            let components = p.dedot_components(cwd).unwrap();
+
+            assert_eq!(components.len(), p.parse_dot_from(cwd).unwrap().components().count());
+            assert_eq!(
+                p.parse_dot_from(cwd).unwrap(),
+                components.collect::<PathBuf>(),
+                "{:?} {:?}",
+                p,
+                cwd
+            );
+        }
+    }
+}
+
+#[test]
+fn dedot_in_place() {
+    for (p, cwd, expected) in [
+        (r"C:\path\to\123\456\.\777", r"C:\", r"C:\path\to\123\456\777"),
+        (r"C:\path\to\..\123\456\.\777\..", r"C:\", r"C:\path\123\456"),
+        ("C:/path/to/../123", r"C:\", r"C:\path\123"),
+        (r"\\server\share\path\..\123", r"C:\", r"\\server\share\123"),
+        (r".\abc", r"C:\foo\bar", r"C:\foo\bar\abc"),
+        (r"..\abc", r"C:\foo\bar", r"C:\foo\abc"),
+        (r"C:.\abc", r"C:\foo", r"C:\foo\abc"),
+        (r"C:\path\to\123", r"C:\", r"C:\path\to\123"),
+    ] {
+        let mut path = PathBuf::from(p);
+
+        let changed = path.dedot_in_place_from(cwd).unwrap();
+
+        assert_eq!(PathBuf::from(expected), path, "{}", p);
+        assert_eq!(p != expected, changed, "{}", p);
+
+        let mut path = OsString::from(p);
+
+        assert_eq!(p != expected, path.dedot_in_place_from(cwd).unwrap(), "{}", p);
+        assert_eq!(OsString::from(expected), path, "{}", p);
+    }
+}
+
+#[test]
+fn keep_allocation() {
+    for p in [r"C:\path\to\..\123\.\456\..", r"path\\to\..\.\123\", r"\..\..\a", r"C:\path\to\123"]
+    {
+        let mut path = PathBuf::from(p);
+
+        path.reserve(64);
+
+        let pointer = path.as_os_str().as_encoded_bytes().as_ptr();
+        let capacity = path.capacity();
+
+        path.dedot_in_place_from(r"C:\cwd").unwrap();
+
+        assert_eq!(pointer, path.as_os_str().as_encoded_bytes().as_ptr(), "{}", p);
+        assert_eq!(capacity, path.capacity(), "{}", p);
+    }
+}
+
+#[test]
+fn same_as_parse_dot() {
+    for prefix in ["", "C:", r"\\?\C:", r"\\server\share"] {
+        // every path of up to 5 characters after the prefix
+        let alphabet = ["a", ".", r"\", "/"];
+
+        let mut paths = vec![String::from(prefix)];
+
+        for _ in 0..5 {
+            paths = paths
+                .iter()
+                .flat_map(|p| alphabet.iter().map(move |c| format!("{}{}", p, c)))
+                .collect();
+
+            for p in paths.iter() {
+                for cwd in [r"C:\cwd\x", r"\\server\share", r"x\y"] {
+                    let expected = PathBuf::from(p).parse_dot_from(cwd).unwrap().into_owned();
+
+                    let mut path = PathBuf::from(p);
+
+                    let changed = path.dedot_in_place_from(cwd).unwrap();
+
+                    assert_eq!(expected, path, "{} {}", p, cwd);
+                    assert_eq!(expected.as_os_str() != p.as_str(), changed, "{} {}", p, cwd);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn dedot_in_place_with_cwd() {
+    let cwd = env::current_dir().unwrap();
+
+    let mut path = PathBuf::from(r".\path\..\123");
+
+    assert!(path.dedot_in_place().unwrap());
+    assert_eq!(cwd.join("123"), path);
+}
+
+#[test]
+fn needs_cwd() {
+    for p in [r".\path", r"..\path", r"C:..\path", r"C:.\path", "."] {
+        assert!(Path::new(p).needs_cwd(), "{}", p);
+    }
+
+    for p in [r"C:\path\..\123", r"\path\..\123", r"C:path\..", "", r"\\server\share\..", r"~\path"]
+    {
+        assert!(!Path::new(p).needs_cwd(), "{}", p);
+        assert!(!Path::new(p).to_path_buf().needs_cwd(), "{}", p);
+    }
+}
+
+#[test]
+fn lazy_cwd() {
+    let mut dedotter = Dedotter::new();
+
+    for p in [r"C:\path\to\..\123", r"path\..\..\123", r"\\server\share\path\..", r"C:path\.."] {
+        assert_eq!(Path::new(p).parse_dot_from(r"C:\cwd").unwrap(), dedotter.parse_dot(p).unwrap());
+    }
+
+    assert!(!dedotter.has_cwd());
+}
+
+#[test]
+fn given_cwd() {
+    let mut dedotter = Dedotter::new().cwd(r"C:\cwd\sub");
+
+    assert!(dedotter.has_cwd());
+
+    let paths = [r".\path", r"..\path", r"C:\path\..\123"];
+
+    let results: Vec<_> = dedotter.parse_dot_all(paths).map(Result::unwrap).collect();
+
+    assert_eq!(
+        vec![Path::new(r"C:\cwd\sub\path"), Path::new(r"C:\cwd\path"), Path::new(r"C:\123")],
+        results
+    );
+}
+
+#[test]
+fn dedotter_reuse() {
+    let mut dedotter = Dedotter::new().cwd(r"C:\cwd");
+
+    // the results do not borrow the dedotter
+    let results: Vec<_> =
+        dedotter.parse_dot_all([r".\a", r"C:\b\..\c"]).map(Result::unwrap).collect();
+
+    assert_eq!(Path::new(r"C:\cwd\d"), dedotter.parse_dot(r".\d").unwrap());
+    assert_eq!(vec![Path::new(r"C:\cwd\a"), Path::new(r"C:\c")], results);
+}
+
+#[test]
+fn dedotter_options() {
+    let mut dedotter =
+        Dedotter::new().options(DedotOptions::new().escape_policy(EscapePolicy::Error)).cwd(r"C:\");
+
+    let results: Vec<_> =
+        dedotter.parse_dot_all([r"C:\a\..\b", r"C:\a\..\..\b", r"..\b"]).collect();
+
+    assert_eq!(Path::new(r"C:\b"), results[0].as_ref().unwrap());
+    assert!(results[1].is_err());
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn fetch_cwd() {
+    let cwd = env::current_dir().unwrap();
+
+    let mut dedotter = Dedotter::new();
+
+    assert_eq!(Path::new(r"C:\123"), dedotter.parse_dot(r"C:\path\..\123").unwrap());
+    assert!(!dedotter.has_cwd());
+
+    assert_eq!(cwd.join("123"), dedotter.parse_dot(r".\path\..\123").unwrap());
+    assert!(dedotter.has_cwd());
+}
+
+#[test]
+fn var_parse_dot() {
+    let mut source = HashMap::new();
+
+    source.insert("APPDATA".to_string(), OsString::from(r"C:\Users\magiclen\AppData\Roaming"));
+    source.insert("UP".to_string(), OsString::from(r"..\.."));
+
+    let expander = VarExpander::with_source(VarSyntax::Windows, source);
+
+    assert_eq!(
+        r"C:\Users\magiclen\AppData\Local",
+        expander
+            .parse_dot_from(Path::new(r"%APPDATA%\..\Local"), r"C:\", DedotOptions::new())
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+    assert_eq!(
+        "logs",
+        expander
+            .parse_dot_from(Path::new(r"a\%UP%\logs"), r"C:\", DedotOptions::new())
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+}
+
+fn parse_escape(p: &str, cwd: &str, escape_policy: EscapePolicy) -> io::Result<String> {
+    let options = DedotOptions::new().escape_policy(escape_policy);
+
+    Path::new(p).parse_dot_from_with(cwd, options).map(|p| p.to_str().unwrap().to_string())
+}
+
+fn escape_index(p: &str, cwd: &str) -> usize {
+    let error = parse_escape(p, cwd, EscapePolicy::Error).unwrap_err();
+
+    assert_eq!(ErrorKind::InvalidInput, error.kind());
+
+    let error = DedotError::downcast_ref(&error).unwrap();
+
+    assert_eq!(Path::new(p), error.path());
+
+    error.index()
+}
+
+#[test]
+fn escape_clamp() {
+    assert_eq!(
+        r"123\456",
+        parse_escape(r"path\to\..\..\..\..\123\456", r"C:\foo", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(
+        r"C:\123\456",
+        parse_escape(r"C:\path\..\..\123\456", r"C:\foo", EscapePolicy::Clamp).unwrap()
+    );
+    assert_eq!(r"C:\abc", parse_escape(r"C:..\abc", r"C:\", EscapePolicy::Clamp).unwrap());
+}
+
+#[test]
+fn escape_error() {
+    assert_eq!("123", parse_escape(r"path\..\123", r"C:\foo", EscapePolicy::Error).unwrap());
+
+    assert_eq!(2, escape_index(r"path\..\..\123", r"C:\foo"));
+    assert_eq!(4, escape_index(r"C:\path\..\..\123", r"C:\foo"));
+    assert_eq!(4, escape_index(r"\\server\share\path\..\..\123", r"C:\foo"));
+    assert_eq!(1, escape_index(r"C:..\abc", r"C:\"));
+    assert_eq!(0, escape_index(r"..\abc", r"C:\"));
+}
+
+#[test]
+fn escape_preserve() {
+    assert_eq!(
+        r"..\..\123\456",
+        parse_escape(r"path\to\..\..\..\..\123\456", r"C:\foo", EscapePolicy::Preserve).unwrap()
+    );
+    assert_eq!(
+        r"C:\123\456",
+        parse_escape(r"C:\path\..\..\123\456", r"C:\foo", EscapePolicy::Preserve).unwrap()
+    );
+    assert_eq!(
+        r"C:..\abc",
+        parse_escape(r"C:path\..\..\abc", r"C:\foo", EscapePolicy::Preserve).unwrap()
+    );
+}
+
+#[test]
+fn from_file_url_disk() {
+    assert_eq!(Path::new(r"C:\path\777"), url::from_file_url("file:///C:/path/to/../777").unwrap());
+    assert_eq!(Path::new(r"C:\path"), url::from_file_url("file:///c|/path/").unwrap());
+    assert_eq!(Path::new(r"C:\"), url::from_file_url("file:///C:").unwrap());
+    assert_eq!(Path::new(r"C:\"), url::from_file_url("file://localhost/C:/../..").unwrap());
+    assert_eq!(Path::new(r"C:\my docs"), url::from_file_url("file:///C:/my%20docs?q#f").unwrap());
+}
+
+#[test]
+fn from_file_url_unc() {
+    assert_eq!(
+        Path::new(r"\\server\share\777"),
+        url::from_file_url("file://server/share/path/../777").unwrap()
+    );
+    assert_eq!(
+        Path::new(r"\\server\share\777"),
+        url::from_file_url("file:////server/share/%2e%2e/777").unwrap()
+    );
+}
+
+#[test]
+fn from_file_url_error() {
+    for (u, error) in [
+        ("http://host/C:/path", FileUrlError::NotFileScheme),
+        ("file:///C:/path/%2", FileUrlError::InvalidPercentEncoding(16)),
+        ("file:///C:/a%5Cb", FileUrlError::EncodedSeparator(12)),
+        ("file:///C:/%FF", FileUrlError::NotUtf8),
+        ("file:///path", FileUrlError::NotAbsolute),
+        ("file://server", FileUrlError::NotAbsolute),
+    ] {
+        assert_eq!(error, url::from_file_url(u).unwrap_err(), "{}", u);
+    }
+}
+
+#[test]
+fn to_file_url() {
+    assert_eq!("file:///C:/path/777", url::to_file_url(Path::new(r"C:\path\to\..\777")).unwrap());
+    assert_eq!("file:///C:/", url::to_file_url(Path::new(r"C:\path\..")).unwrap());
+    assert_eq!("file:///C:/my%20docs", url::to_file_url(Path::new(r"\\?\C:\my docs")).unwrap());
+    assert_eq!(
+        "file://server/share/777",
+        url::to_file_url(Path::new(r"\\server\share\path\..\777")).unwrap()
+    );
+    assert_eq!(
+        "file://server/share/777",
+        url::to_file_url(Path::new(r"\\?\UNC\server\share\777")).unwrap()
+    );
+
+    for (p, error) in [
+        (r"\path", FileUrlError::NotAbsolute),
+        (r"C:path", FileUrlError::NotAbsolute),
+        (r"\\?\pipe\name", FileUrlError::UnsupportedPrefix),
+        (r"\\.\COM1", FileUrlError::UnsupportedPrefix),
+    ] {
+        assert_eq!(error, url::to_file_url(Path::new(p)).unwrap_err(), "{}", p);
+    }
+}
+
+#[test]
+fn file_url_round_trip() {
+    for p in [r"C:\", r"C:\path\to\777", r"C:\my docs\100%\a#b", r"\\server\share\777"] {
+        let url = url::to_file_url(Path::new(p)).unwrap();
+
+        assert_eq!(Path::new(p), url::from_file_url(&url).unwrap(), "{}", url);
+    }
+}
+
+/// Create a path from UTF-16 code units, where `0xD800` and `0xDC00` are unpaired surrogates.
+fn wide(s: &str) -> PathBuf {
+    let units: Vec<u16> = s
+        .chars()
+        .map(|c| match c {
+            'L' => 0xD800,
+            'T' => 0xDC00,
+            c => c as u16,
+        })
+        .collect();
+
+    PathBuf::from(OsString::from_wide(&units))
+}
+
+#[test]
+fn ill_formed_utf16_parse_dot() {
+    let cwd = wide(r"C:\L\cwd");
+
+    assert!(cwd.to_str().is_none());
+
+    for (p, expected) in [
+        (r"C:.\L", r"C:\L\cwd\L"),
+        (r"C:..\T", r"C:\L\T"),
+        (r".\L\..\T", r"C:\L\cwd\T"),
+        (r"\\L\T\a\..\L", r"\\L\T\L"),
+        (r"\\?\L\a\..\b", r"\\?\L\b"),
+        (r"C:\L\.\T\..", r"C:\L"),
+    ] {
+        let p = wide(p);
+        let expected = wide(expected);
+
+        assert_eq!(expected, p.parse_dot_from(&cwd).unwrap(), "{:?}", p);
+        assert!(!p.is_dedotted(), "{:?}", p);
+        assert!(expected.is_dedotted(), "{:?}", p);
+
+        let mut path = p.clone();
+
+        assert!(path.dedot_in_place_from(&cwd).unwrap(), "{:?}", p);
+        assert_eq!(expected, path);
+    }
+}
+
+#[test]
+fn ill_formed_utf16_clean() {
+    assert_eq!(wide(r"C:L"), wide(r"C:.\L").clean());
+    assert_eq!(wide(r"..\T"), wide(r"L\..\..\T").clean());
+}
+
+#[test]
+fn ill_formed_utf16_relative_to() {
+    assert_eq!(wide(r"..\T"), wide(r"C:\L\T").relative_to(wide(r"C:\L\L"), r"C:\").unwrap());
+}
+
+#[test]
+fn ill_formed_utf16_expand_var() {
+    let mut source = HashMap::new();
+
+    source.insert("DIR".to_string(), wide(r"x\T").into_os_string());
+
+    let expander = VarExpander::with_source(VarSyntax::Windows, source);
+
+    assert_eq!(wide(r"L\x\T\L"), expander.expand(&wide(r"L\%DIR%\L")).unwrap());
+}
+
+#[test]
+fn is_dedotted() {
+    for p in [
+        r"C:\path\to\123",
+        "C:/path/to/123",
+        r"\\server\share",
+        r"\\server\share\",
+        "C:",
+        "",
+        r"C:\path\\123",
+        r"C:\path\",
+        r"C:.\path",
+        r"\\?\C:\.",
+        "..",
+    ] {
+        let p = Path::new(p);
+
+        let borrowed = matches!(p.parse_dot_from(r"C:\cwd").unwrap(), Cow::Borrowed(_));
+
+        assert_eq!(borrowed, p.is_dedotted(), "{:?}", p);
+        assert_eq!(borrowed, p.to_path_buf().is_dedotted(), "{:?}", p);
+    }
+}
+
+#[test]
+fn physical_missing() {
+    let root = env::temp_dir().join(format!("path-dedot-physical-{}", std::process::id()));
+
+    let _ = fs::remove_dir_all(&root);
+
+    fs::create_dir_all(root.join(r"real\dir")).unwrap();
+
+    let canonical_root = fs::canonicalize(&root).unwrap();
+
+    assert_eq!(
+        canonical_root.join(r"real\a\c"),
+        root.join(r"real\dir\..\a\b\..\c").parse_dot_physical().unwrap()
+    );
+    assert_eq!(
+        canonical_root.join(r"real\dir\x"),
+        PathBuf::from(r".\dir\x").parse_dot_physical_from(root.join("real")).unwrap()
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+fn relative(target: &str, base: &str, cwd: &str) -> String {
+    Path::new(target).relative_to(base, cwd).unwrap().to_str().unwrap().to_string()
+}
+
+#[test]
+fn relative_to_lv0() {
+    assert_eq!(".", relative(r"C:\a\b", r"C:\a\b", r"C:\"));
+    assert_eq!(r"c\d", relative(r"C:\a\b\c\d", r"C:\a\b", r"C:\"));
+    assert_eq!(r"..\..", relative(r"C:\a\b", r"c:\a\b\c\d", r"C:\"));
+    assert_eq!(r"..\..\x\y", relative(r"C:\a\b\x\y", r"C:\a\b\c\d", r"C:\"));
+    assert_eq!(r"..\x", relative(r"\\server\share\x", r"\\server\share\y", r"C:\"));
+    assert_eq!(r"..\a", relative(r"\\Server\share\a", r"\\server\SHARE\b", r"C:\"));
+    assert_eq!(r"..\a", relative(r"\\?\UNC\Server\share\a", r"\\?\UNC\server\share\b", r"C:\"));
+}
+
+#[test]
+fn relative_to_lv1() {
+    assert_eq!(r"..\x", relative(r".\x", r".\y", r"C:\foo"));
+    assert_eq!(r"..\foo\x", relative(r".\x", r"..\y", r"C:\foo"));
+}
+
+#[test]
+fn relative_to_error() {
+    assert!(Path::new(r"C:\a").relative_to(r"D:\a", r"C:\").is_err());
+    assert!(Path::new(r"\\server\share\a").relative_to(r"\\server\other\a", r"C:\").is_err());
+    assert!(Path::new(r"C:\a").relative_to(r"\\server\share\a", r"C:\").is_err());
+    assert!(Path::new(r"C:\a").relative_to(r"C:a", r"C:\").is_err());
+
+    let error = Path::new(r"C:\a").relative_to(r"D:\a", r"C:\").unwrap_err();
+
+    match DedotError::downcast_ref(&error) {
+        Some(DedotError::PrefixMismatch {
+            path,
+            base,
+            index,
+        }) => {
+            assert_eq!(Path::new(r"C:\a"), path);
+            assert_eq!(Path::new(r"D:\a"), base);
+            assert_eq!(0, *index);
+        },
+        _ => panic!("{:?}", error),
+    }
+
+    // the prefixes are the same, but the roots are not
+    let error = Path::new(r"C:\a").relative_to(r"C:a", r"C:\").unwrap_err();
+
+    assert_eq!(1, DedotError::downcast_ref(&error).unwrap().index());
+}
+
+#[test]
+fn symlink_aware_lexical() {
+    let result = PathBuf::from(r"C:\path-dedot-missing\to\..\..\..\123\.\456")
+        .parse_dot_from_symlink_aware(r"C:\", SymlinkPolicy::Error)
+        .unwrap();
+
+    assert_eq!(PathBuf::from(r"C:\123\456"), result.path);
+    assert_eq!(
+        vec![PathBuf::from(r"C:\path-dedot-missing\to"), PathBuf::from(r"C:\path-dedot-missing")],
+        result.checked.into_iter().map(|c| c.path).collect::<Vec<_>>()
+    );
+
+    let result = PathBuf::from(r"..\path-dedot-missing\..\x")
+        .parse_dot_from_symlink_aware(r"C:\path-dedot-missing\y", SymlinkPolicy::Error)
+        .unwrap();
+
+    assert_eq!(PathBuf::from(r"C:\path-dedot-missing\x"), result.path);
+}
+
+#[test]
+fn tilde() {
+    env::set_var("HOME", r"C:\Users\path-dedot");
+
+    let options = DedotOptions::new().expand_tilde(true);
+
+    assert_eq!(
+        r"C:\Users\path-dedot\a",
+        expand_tilde(Path::new(r"~\a")).unwrap().to_str().unwrap()
+    );
+    assert_eq!(
+        r"C:\Users\b",
+        Path::new(r"~\..\b").parse_dot_from_with(r"C:\", options).unwrap().to_str().unwrap()
+    );
+    assert_eq!(
+        r"a\~\b",
+        Path::new(r"a\~\b").parse_dot_from_with(r"C:\", options).unwrap().to_str().unwrap()
+    );
+}
+
+#[test]
+fn tilde_user() {
+    assert_eq!(
+        TildeError::UserUnsupported(OsString::from("someone")),
+        expand_tilde(Path::new(r"~someone\a")).unwrap_err()
+    );
+}
+
+fn parse_trailing(p: &str, cwd: &str) -> Cow<'static, str> {
+    let options = DedotOptions::new().keep_trailing_separator(true);
+
+    let p: &'static Path = Box::leak(Path::new(p).to_path_buf().into_boxed_path());
+
+    match p.parse_dot_from_with(cwd, options).unwrap() {
+        Cow::Borrowed(p) => Cow::Borrowed(p.to_str().unwrap()),
+        Cow::Owned(p) => Cow::Owned(p.into_os_string().into_string().unwrap()),
+    }
+}
+
+#[test]
+fn trailing_separator() {
+    assert_eq!(r"dir\", parse_trailing(r"dir\", r"C:\foo"));
+    assert_eq!(r"dir\", parse_trailing(r"dir\sub\..", r"C:\foo"));
+    assert_eq!(r"C:\dir\sub\", parse_trailing(r"C:\dir\sub\.", r"C:\foo"));
+    assert_eq!(r"C:\foo\", parse_trailing(r".\", r"C:\foo"));
+    assert_eq!(r"C:\", parse_trailing(r"C:\dir\..", r"C:\foo"));
+    assert_eq!("C:", parse_trailing(r"C:dir\..", r"C:\foo"));
+    assert_eq!(r"C:dir\", parse_trailing(r"C:dir\sub\..", r"C:\foo"));
+    assert_eq!(r"\\server\share\", parse_trailing(r"\\server\share\dir\..", r"C:\foo"));
+}
+
+#[test]
+fn trailing_separator_borrowed() {
+    assert!(matches!(parse_trailing(r"dir\", r"C:\foo"), Cow::Borrowed(_)));
+    assert!(matches!(parse_trailing(r"C:\dir\sub\", r"C:\foo"), Cow::Borrowed(_)));
+    assert!(matches!(parse_trailing(r"C:\", r"C:\foo"), Cow::Borrowed(_)));
+}
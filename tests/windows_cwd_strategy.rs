@@ -0,0 +1,48 @@
+#![cfg(windows)]
+
+use std::{env, path::Path};
+
+use path_dedot::{cwd_strategy, set_cwd_strategy, update_cwd, CwdStrategy, ParseDot};
+
+#[test]
+fn switch_strategy() {
+    let cache_by_default = cfg!(any(
+        feature = "once_cell_cache",
+        feature = "lazy_static_cache",
+        feature = "unsafe_cache"
+    ));
+
+    assert_eq!(
+        if cache_by_default { CwdStrategy::Cache } else { CwdStrategy::Fetch },
+        cwd_strategy()
+    );
+
+    let first = env::current_dir().unwrap();
+    let second = env::temp_dir();
+
+    let p = Path::new(r".\path\to\123\456");
+
+    set_cwd_strategy(CwdStrategy::Cache);
+    assert_eq!(CwdStrategy::Cache, cwd_strategy());
+
+    update_cwd().unwrap();
+
+    assert_eq!(first.join(r"path\to\123\456"), p.parse_dot().unwrap());
+
+    // the cached CWD is kept until it is updated
+    env::set_current_dir(&second).unwrap();
+
+    assert_eq!(first.join(r"path\to\123\456"), p.parse_dot().unwrap());
+
+    update_cwd().unwrap();
+
+    assert_eq!(env::current_dir().unwrap().join(r"path\to\123\456"), p.parse_dot().unwrap());
+
+    // the CWD is fetched every time
+    set_cwd_strategy(CwdStrategy::Fetch);
+    assert_eq!(CwdStrategy::Fetch, cwd_strategy());
+
+    env::set_current_dir(&first).unwrap();
+
+    assert_eq!(first.join(r"path\to\123\456"), p.parse_dot().unwrap());
+}
@@ -0,0 +1,445 @@
+use std::{
+    borrow::Cow,
+    ffi::{OsStr, OsString},
+    path::{Path, Prefix},
+};
+
+use path_dedot::{windows, DedotError, DedotOptions, EscapePolicy};
+
+#[test]
+fn dedot_lv1() {
+    assert_eq!(
+        r"\path\to\123\456\777",
+        windows::parse_dot_from(r"\path\to\123\456\.\777", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"C:\path\to\123\456\777",
+        windows::parse_dot_from(r"C:\path\to\123\456\.\777", r"C:\").unwrap()
+    );
+}
+
+#[test]
+fn dedot_lv2() {
+    assert_eq!(
+        r"\path\to\123\777",
+        windows::parse_dot_from(r"\path\to\123\456\..\777", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"C:\path\to\123\777",
+        windows::parse_dot_from(r"C:\path\to\123\456\..\777", r"C:\").unwrap()
+    );
+}
+
+#[test]
+fn dedot_lv3() {
+    assert_eq!(
+        r"\path\123\456",
+        windows::parse_dot_from(r"\path\to\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"C:\path\123\456",
+        windows::parse_dot_from(r"C:\path\to\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"path\123\456",
+        windows::parse_dot_from(r"path\to\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"C:path\123\456",
+        windows::parse_dot_from(r"C:path\to\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+}
+
+#[test]
+fn dedot_lv4() {
+    assert_eq!(
+        r"123\456",
+        windows::parse_dot_from(r"path\to\..\..\..\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"C:123\456",
+        windows::parse_dot_from(r"C:path\to\..\..\..\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"\123\456",
+        windows::parse_dot_from(r"\path\to\..\..\..\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"C:\123\456",
+        windows::parse_dot_from(r"C:\path\to\..\..\..\..\123\456\.\777\..", r"C:\").unwrap()
+    );
+}
+
+#[test]
+fn dedot_lv5() {
+    for cwd in [r"\foo\bar\baz", r"foo\bar\baz"] {
+        assert_eq!(r"C:\", windows::parse_dot_from(r"C:\", cwd).unwrap());
+        assert_eq!("C:", windows::parse_dot_from("C:", cwd).unwrap());
+        assert_eq!("", windows::parse_dot_from("", cwd).unwrap());
+        assert_eq!("abc", windows::parse_dot_from("abc", cwd).unwrap());
+    }
+}
+
+#[test]
+fn dedot_lv6() {
+    for p in [r".\abc", r"..\abc"] {
+        assert_eq!(r"\abc", windows::parse_dot_from(p, r"\").unwrap());
+        assert_eq!("abc", windows::parse_dot_from(p, "").unwrap());
+        assert_eq!(r"C:\abc", windows::parse_dot_from(p, r"C:\").unwrap());
+        assert_eq!("C:abc", windows::parse_dot_from(p, "C:").unwrap());
+    }
+
+    assert_eq!(r"\foo\bar\baz\abc", windows::parse_dot_from(r".\abc", r"\foo\bar\baz").unwrap());
+    assert_eq!(r"foo\bar\baz\abc", windows::parse_dot_from(r".\abc", r"foo\bar\baz").unwrap());
+    assert_eq!(
+        r"C:\foo\bar\baz\abc",
+        windows::parse_dot_from(r".\abc", r"C:\foo\bar\baz").unwrap()
+    );
+    assert_eq!(r"C:foo\bar\baz\abc", windows::parse_dot_from(r".\abc", r"C:foo\bar\baz").unwrap());
+
+    assert_eq!(r"\foo\bar\abc", windows::parse_dot_from(r"..\abc", r"\foo\bar\baz").unwrap());
+    assert_eq!(r"foo\bar\abc", windows::parse_dot_from(r"..\abc", r"foo\bar\baz").unwrap());
+    assert_eq!(r"C:\foo\bar\abc", windows::parse_dot_from(r"..\abc", r"C:\foo\bar\baz").unwrap());
+    assert_eq!(r"C:foo\bar\abc", windows::parse_dot_from(r"..\abc", r"C:foo\bar\baz").unwrap());
+
+    // the result is only a prefix and a root, as long as the path
+    assert_eq!(r"C:\", windows::parse_dot_from("..", r"C:\cwd").unwrap());
+    assert_eq!(r"C:\", windows::parse_dot_from(".", r"C:\").unwrap());
+}
+
+#[test]
+fn dedot_lv7() {
+    for p in [r"C:.\abc", r"C:..\abc"] {
+        assert_eq!(r"C:\abc", windows::parse_dot_from(p, r"\").unwrap());
+        assert_eq!("C:abc", windows::parse_dot_from(p, "").unwrap());
+        assert_eq!(r"C:\abc", windows::parse_dot_from(p, r"C:\").unwrap());
+        assert_eq!("C:abc", windows::parse_dot_from(p, "C:").unwrap());
+    }
+
+    assert_eq!(
+        r"C:\foo\bar\baz\abc",
+        windows::parse_dot_from(r"C:.\abc", r"\foo\bar\baz").unwrap()
+    );
+    assert_eq!(r"C:foo\bar\baz\abc", windows::parse_dot_from(r"C:.\abc", r"foo\bar\baz").unwrap());
+    assert_eq!(
+        r"C:\foo\bar\baz\abc",
+        windows::parse_dot_from(r"C:.\abc", r"C:\foo\bar\baz").unwrap()
+    );
+    assert_eq!(
+        r"C:foo\bar\baz\abc",
+        windows::parse_dot_from(r"C:.\abc", r"C:foo\bar\baz").unwrap()
+    );
+
+    assert_eq!(r"C:\foo\bar\abc", windows::parse_dot_from(r"C:..\abc", r"\foo\bar\baz").unwrap());
+    assert_eq!(r"C:foo\bar\abc", windows::parse_dot_from(r"C:..\abc", r"foo\bar\baz").unwrap());
+    assert_eq!(r"C:\foo\bar\abc", windows::parse_dot_from(r"C:..\abc", r"C:\foo\bar\baz").unwrap());
+    assert_eq!(r"C:foo\bar\abc", windows::parse_dot_from(r"C:..\abc", r"C:foo\bar\baz").unwrap());
+}
+
+#[test]
+fn dedot_separator() {
+    assert_eq!(
+        r"C:\path\to\777",
+        windows::parse_dot_from("C:/path/to/123/../777", r"C:\").unwrap()
+    );
+    assert_eq!(r"path\777", windows::parse_dot_from(r"path//to/..\\777", r"C:\").unwrap());
+    // the prefix is kept as it is
+    assert_eq!(
+        r"//server/share\777",
+        windows::parse_dot_from("//server/share/path/../777", r"C:\").unwrap()
+    );
+}
+
+#[test]
+fn dedot_verbatim() {
+    // `/` is not a separator and `.` is a component in verbatim paths
+    assert_eq!(
+        r"\\?\C:\path\777",
+        windows::parse_dot_from(r"\\?\C:\path\to\..\777", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"\\?\C:\path\a/..\777",
+        windows::parse_dot_from(r"\\?\C:\path\.\a/..\777", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"\\?\UNC\server\share\777",
+        windows::parse_dot_from(r"\\?\UNC\server\share\path\..\777", r"C:\").unwrap()
+    );
+    assert_eq!(
+        r"\\?\pictures\777",
+        windows::parse_dot_from(r"\\?\pictures\path\..\..\777", r"C:\").unwrap()
+    );
+    assert_eq!(r"\\.\COM1\777", windows::parse_dot_from(r"\\.\COM1\path\..\777", r"C:\").unwrap());
+}
+
+#[test]
+fn prefix() {
+    for p in [r"C:\", "C:", r"\\VBOXSRV\test", r"\\VBOXSRV\test\", r"\\?\C:\", r"\\.\COM1"] {
+        assert!(matches!(windows::parse_dot_from(p, r"C:\").unwrap(), Cow::Borrowed(_)), "{}", p);
+    }
+
+    assert_eq!(Some(Prefix::Disk(b'C')), windows::parse_prefix(r"c:\path"));
+    assert_eq!(Some(Prefix::VerbatimDisk(b'C')), windows::parse_prefix(r"\\?\C:\path"));
+    assert_eq!(Some(Prefix::Verbatim(OsStr::new("C:path"))), windows::parse_prefix(r"\\?\C:path"));
+    assert_eq!(
+        Some(Prefix::VerbatimUNC(OsStr::new("server"), OsStr::new("share"))),
+        windows::parse_prefix(r"\\?\UNC\server\share\path")
+    );
+    assert_eq!(Some(Prefix::DeviceNS(OsStr::new("COM1"))), windows::parse_prefix("//./COM1"));
+    assert_eq!(
+        Some(Prefix::UNC(OsStr::new("server"), OsStr::new("share"))),
+        windows::parse_prefix("//server/share")
+    );
+    assert_eq!(
+        Some(Prefix::UNC(OsStr::new("?"), OsStr::new("C:"))),
+        windows::parse_prefix("//?/C:/path")
+    );
+    assert_eq!(None, windows::parse_prefix(r"\\server"));
+    assert_eq!(None, windows::parse_prefix(r"\path"));
+    assert_eq!(None, windows::parse_prefix("1:"));
+}
+
+#[test]
+fn clean() {
+    assert_eq!(".", windows::clean(""));
+    assert_eq!(".", windows::clean("."));
+    assert_eq!(r"\", windows::clean(r"\"));
+    assert_eq!("..", windows::clean(".."));
+    assert_eq!(r"path\to\123\456", windows::clean(r".\path\to\123\456"));
+    assert_eq!(r"..\..\123\456", windows::clean(r"path\to\..\..\..\..\123\456\.\777\.."));
+    assert_eq!(r"C:\123\456", windows::clean(r"C:\path\to\..\..\..\..\123\456\.\777\.."));
+    assert_eq!(r"\\server\share\123", windows::clean(r"\\server\share\path\..\..\123"));
+    assert_eq!(r"C:..\path", windows::clean(r"C:..\path"));
+    assert_eq!(r"C:path", windows::clean(r"C:.\path"));
+    assert_eq!(".", windows::clean(r"path\.."));
+    assert_eq!(r"C:\", windows::clean(r"C:\.."));
+
+    // nothing is changed
+    assert!(matches!(windows::clean("."), Cow::Borrowed(_)));
+}
+
+#[test]
+fn clean_name_like_prefix() {
+    // a relative path must not become a prefixed one
+    assert_eq!(r".\C:\x", windows::clean(r".\C:\x"));
+    assert_eq!(r".\C:x", windows::clean(r".\C:x"));
+    assert_eq!(r".\c:", windows::clean(r"a\..\c:"));
+    assert_eq!(r".\C:\x", windows::clean(r"a\..\.\C:\x"));
+    assert_eq!(r"C:D:x", windows::clean(r"C:.\D:x"));
+
+    assert!(matches!(windows::clean(r".\C:\x"), Cow::Borrowed(_)));
+
+    for p in [r".\C:\x", r".\C:x", r"a\..\c:", r"a/../C:/x", r"a\..\\\server\share"] {
+        let cleaned = windows::clean(p);
+
+        assert_eq!(cleaned, windows::clean(cleaned.as_ref()), "{}", p);
+    }
+}
+
+#[test]
+fn escape_policy() {
+    let parse = |p, cwd, escape_policy| {
+        windows::parse_dot_from_with(p, cwd, DedotOptions::new().escape_policy(escape_policy))
+    };
+
+    assert_eq!(r"C:\abc", parse(r"C:..\abc", r"C:\", EscapePolicy::Clamp).unwrap());
+    assert_eq!(r"C:..\abc", parse(r"C:path\..\..\abc", r"C:\foo", EscapePolicy::Preserve).unwrap());
+    assert_eq!(
+        r"..\..\123",
+        parse(r"path\to\..\..\..\..\123", r"C:\foo", EscapePolicy::Preserve).unwrap()
+    );
+
+    for (p, cwd, index) in [
+        (r"path\..\..\123", r"C:\foo", 2),
+        (r"C:\path\..\..\123", r"C:\foo", 4),
+        (r"\\server\share\path\..\..\123", r"C:\foo", 4),
+        (r"C:..\abc", r"C:\", 1),
+        (r"..\abc", r"C:\", 0),
+    ] {
+        let error = parse(p, cwd, EscapePolicy::Error).unwrap_err();
+
+        let error = DedotError::downcast_ref(&error).unwrap();
+
+        assert_eq!(Path::new(p), error.path());
+        assert_eq!(index, error.index());
+    }
+}
+
+#[test]
+fn trailing_separator() {
+    let parse = |p, cwd| {
+        windows::parse_dot_from_with(p, cwd, DedotOptions::new().keep_trailing_separator(true))
+    };
+
+    assert_eq!(r"dir\", parse(r"dir\sub\..", r"C:\foo").unwrap());
+    assert_eq!(r"C:\foo\", parse(r".\", r"C:\foo").unwrap());
+    assert_eq!("C:", parse(r"C:dir\..", r"C:\foo").unwrap());
+    assert_eq!(r"\\server\share\", parse(r"\\server\share\dir\..", r"C:\foo").unwrap());
+}
+
+#[test]
+fn os_str() {
+    let p = OsStr::new(r"C:\path\to\..\777");
+
+    assert_eq!(
+        OsString::from(r"C:\path\777"),
+        windows::parse_dot_from(p, OsStr::new(r"C:\")).unwrap()
+    );
+    assert_eq!(OsStr::new(r"C:\path\777"), windows::clean(p));
+}
+
+#[cfg(windows)]
+#[test]
+fn prefix_same_as_native() {
+    use std::path::Component;
+
+    for p in [
+        "",
+        r"\",
+        "abc",
+        "C:",
+        r"c:\abc",
+        "1:",
+        r"\\server",
+        r"\\server\share",
+        "//server/share/abc",
+        r"\\?\C:",
+        r"\\?\C:\abc",
+        r"\\?\C:abc",
+        r"\\?\UNC\server\share",
+        r"\\?\UNC\server",
+        r"\\?\pictures\abc",
+        r"\\.\COM1",
+        "//./COM1",
+        "//?/C:/abc",
+        r"\\?/C:\abc",
+    ] {
+        let native = match Path::new(p).components().next() {
+            Some(Component::Prefix(prefix)) => Some(prefix.kind()),
+            _ => None,
+        };
+
+        assert_eq!(native, windows::parse_prefix(p), "{}", p);
+    }
+}
+
+#[test]
+fn bytes() {
+    let p: &[u8] = b"C:\\path\\to\\\xFF\\..\\777";
+
+    assert_eq!(b"C:\\path\\to\\777", &*windows::parse_dot_from(p, b"C:\\".as_slice()).unwrap());
+    assert_eq!(b"C:\\path\\to\\777", &*windows::clean(p));
+}
+
+#[test]
+fn ill_formed_utf16() {
+    // the WTF-8 bytes of unpaired surrogates, which `OsStr` uses for ill-formed UTF-16 on Windows
+    let lead: &[u8] = b"\xED\xA0\x80";
+    let trail: &[u8] = b"\xED\xB0\x80";
+
+    let join = |parts: &[&[u8]]| parts.concat();
+
+    let cwd = join(&[br"C:\", lead, br"\cwd"]);
+
+    for (p, expected) in [
+        (join(&[br"C:.\", lead]), join(&[br"C:\", lead, br"\cwd\", lead])),
+        (join(&[br"C:..\", trail]), join(&[br"C:\", lead, br"\", trail])),
+        (join(&[br".\", lead, br"\..\", trail]), join(&[br"C:\", lead, br"\cwd\", trail])),
+        (
+            join(&[br"\\", lead, br"\", trail, br"\a\..\", lead]),
+            join(&[br"\\", lead, br"\", trail, br"\", lead]),
+        ),
+        (join(&[br"\\?\", lead, br"\a\..\b"]), join(&[br"\\?\", lead, br"\b"])),
+    ] {
+        assert_eq!(expected, &*windows::parse_dot_from(p.as_slice(), cwd.as_slice()).unwrap());
+        assert!(!windows::is_dedotted(p.as_slice()));
+        assert!(windows::is_dedotted(expected.as_slice()));
+
+        let mut path = p.clone();
+
+        assert!(windows::dedot_in_place_from(&mut path, &cwd).unwrap());
+        assert_eq!(expected, path);
+    }
+
+    assert_eq!(join(&[br"C:", lead]), &*windows::clean(join(&[br"C:.\", lead]).as_slice()));
+}
+
+#[test]
+fn is_dedotted() {
+    for p in [r"C:\path\to\123", "C:/path/to/123", r"\\server\share", r"\\?\C:\", "C:", "", "abc"] {
+        assert!(windows::is_dedotted(p), "{}", p);
+    }
+
+    for p in [r"C:\path\..\123", r"C:\path\\123", r"C:\path\", r"C:.\path", r"\\?\C:\.", "."] {
+        assert!(!windows::is_dedotted(p), "{}", p);
+    }
+
+    // every string of up to 5 characters
+    let alphabet = ["a", ".", r"\", "/", ":", "?", "C"];
+
+    let mut paths = vec![String::new()];
+
+    for _ in 0..5 {
+        paths = paths
+            .iter()
+            .flat_map(|p| alphabet.iter().map(move |c| format!("{}{}", p, c)))
+            .collect();
+
+        for p in paths.iter().map(String::as_str) {
+            let borrowed =
+                matches!(windows::parse_dot_from(p, r"C:\cwd").unwrap(), Cow::Borrowed(_));
+
+            assert_eq!(borrowed, windows::is_dedotted(p), "{}", p);
+        }
+    }
+}
+
+#[test]
+fn dedot_in_place() {
+    for (p, cwd, expected) in [
+        (r"C:\path\to\..\123\.\456\..", r"C:\cwd", r"C:\path\123"),
+        (r"C:.\abc", r"C:\foo", r"C:\foo\abc"),
+        (r"\\?\C:\a/..\b", r"C:\cwd", r"\\?\C:\a/..\b"),
+        (r"..\abc", r"C:\foo\bar", r"C:\foo\abc"),
+    ] {
+        let mut path = p.as_bytes().to_vec();
+
+        let changed = windows::dedot_in_place_from(&mut path, cwd.as_bytes()).unwrap();
+
+        assert_eq!(expected.as_bytes(), path.as_slice(), "{}", p);
+        assert_eq!(p != expected, changed, "{}", p);
+        assert_eq!(expected, windows::parse_dot_from(p, cwd).unwrap(), "{}", p);
+    }
+}
+
+#[test]
+fn needs_cwd() {
+    for p in [r".\path", r"..\path", r"C:..\path", r"C:.\path", r"C:./..", r"."] {
+        assert!(windows::needs_cwd(p), "{}", p);
+    }
+
+    for p in
+        [r"C:\path\..\123", r"\path\..\123", r"C:", r"C:path\..", r"\\server\share\..", r"", r".a"]
+    {
+        assert!(!windows::needs_cwd(p), "{}", p);
+    }
+
+    // every string of up to 5 characters, whose result depends on the CWD exactly when it needs the CWD
+    let alphabet = ["a", ".", r"\", "/", ":", "?", "C"];
+
+    let mut paths = vec![String::new()];
+
+    for _ in 0..5 {
+        paths = paths
+            .iter()
+            .flat_map(|p| alphabet.iter().map(move |c| format!("{}{}", p, c)))
+            .collect();
+
+        for p in paths.iter().map(String::as_str) {
+            let a = windows::parse_dot_from(p, r"C:\a\b\c").unwrap();
+            let b = windows::parse_dot_from(p, r"D:\e\f\g").unwrap();
+
+            assert_eq!(a != b, windows::needs_cwd(p), "{}", p);
+        }
+    }
+}
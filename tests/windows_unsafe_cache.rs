@@ -6,9 +6,7 @@ use path_dedot::{update_cwd, ParseDot, ParsePrefix};
 
 #[test]
 fn dedot_after_updating_cwd() {
-    unsafe {
-        update_cwd();
-    }
+    update_cwd().unwrap();
 
     let p = Path::new(r".\path\to\123\456");
 
@@ -25,9 +23,7 @@ fn dedot_after_updating_cwd() {
 
     env::set_current_dir(Path::new(prefix.as_os_str())).unwrap();
 
-    unsafe {
-        update_cwd();
-    }
+    update_cwd().unwrap();
 
     assert_eq!(
         Path::join(env::current_dir().unwrap().as_path(), Path::new(r"path\to\123\456"))